@@ -0,0 +1,58 @@
+#!/usr/bin/env python3
+
+# Downloads every icon referenced by src/data.json and src/consts.rs into assets/icons
+# and writes assets/icons/manifest.json, which maps the remote URLs to the bundled files.
+# Runs as a pre-build hook of trunk. Icons that fail to download are left out of the manifest,
+# so the app loads them from the wiki instead.
+
+import argparse
+import json
+import re
+import sys
+import urllib.request
+from pathlib import Path
+from urllib.error import URLError
+from urllib.parse import unquote, urlparse
+
+ROOT = Path(__file__).resolve().parent.parent
+
+p = argparse.ArgumentParser()
+p.add_argument("--data", help="Character data", type=Path, default=ROOT / "src/data.json")
+p.add_argument("--consts", help="Constants with icon URLs", type=Path, default=ROOT / "src/consts.rs")
+p.add_argument("--out", help="Output directory", type=Path, default=ROOT / "assets/icons")
+p.add_argument("--force", help="Download icons that already exist", action="store_true")
+p.add_argument("--manifest-only", help="Only write the manifest of the downloaded icons", action="store_true")
+args = p.parse_args()
+
+data = json.loads(args.data.read_text())
+urls = [c["icon"] for c in data["characters"] if c.get("icon")]
+urls += re.findall(r'"(https://[^"]+\.png)"', args.consts.read_text())
+
+args.out.mkdir(parents=True, exist_ok=True)
+manifest_path = args.out / "manifest.json"
+
+manifest = {}
+failed = []
+for url in dict.fromkeys(urls):
+    name = unquote(Path(urlparse(url).path).name)
+    path = args.out / name
+    if not args.manifest_only and (args.force or not path.exists()):
+        print(f"> Downloading {url}", file=sys.stderr)
+        try:
+            with urllib.request.urlopen(url, timeout=30) as r:
+                path.write_bytes(r.read())
+        except (URLError, OSError) as e:
+            print(f"> Failed: {e}", file=sys.stderr)
+            path.unlink(missing_ok=True)
+    # Only icons that exist are bundled, the others are loaded from their URL
+    if path.exists():
+        manifest[url] = f"icons/{name}"
+    else:
+        failed.append(url)
+
+manifest_path.write_text(json.dumps(dict(sorted(manifest.items())), indent=4) + "\n")
+print(f"> Wrote {len(manifest)} icons to {manifest_path}", file=sys.stderr)
+if failed:
+    print(f"> {len(failed)} icons are not bundled and will be loaded from the wiki:", file=sys.stderr)
+    for url in failed:
+        print(f">   {url}", file=sys.stderr)
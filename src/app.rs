@@ -1,13 +1,25 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeSet, HashMap};
 
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use gloo_file::callbacks::FileReader;
 use gloo_storage::{LocalStorage, Storage as _};
+use gloo_timers::callback::Timeout;
 use wasm_bindgen::JsCast;
-use web_sys::{EventTarget, HtmlInputElement};
+use web_sys::{DragEvent, EventTarget, HtmlAnchorElement, HtmlImageElement, HtmlInputElement};
 use yew::{html::Scope, prelude::*};
 
 use crate::logic::{
-    character::{Character, Type},
-    state::{Selected, State, group_characters_by_type},
+    analysis::PLAYER_TYPES,
+    character::{Category, Character, Type},
+    data::{ImportError, export_script},
+    i18n::Language,
+    qr::qr_svg,
+    reveal::{self, Reveal},
+    seating,
+    state::{PendingImport, Selected, SheetScope, State, group_characters_by_type, undealt_types},
+    statistics::{Statistics, simulate},
+    tokens::token_pages,
+    zip::zip,
 };
 
 pub enum Msg {
@@ -18,9 +30,8 @@ pub enum Msg {
     ClearAll,
     SetPlayerCount(u8),
     SetTypeCountsLocked(bool),
-    SetOutsiderCount(u8),
-    SetMinionCount(u8),
-    SetDemonCount(u8),
+    SetTypeCountMin(Type, u8),
+    SetTypeCountMax(Type, u8),
     SetScript(String),
     ToggleScriptMenu,
     UpdateScriptRenameInput(String),
@@ -28,10 +39,63 @@ pub enum Msg {
     DeleteScript,
     UpdateScriptInput(String),
     ImportScript,
+    ImportScriptFiles(Vec<gloo_file::File>),
+    /// reader id, file name and content
+    ScriptFileLoaded(usize, String, Result<String, String>),
+    ConfirmImport(usize),
+    DiscardImport(usize),
+    NewScript,
+    CloneScript,
+    EditScript,
+    UpdateEditorName(String),
+    UpdateEditorSearch(String),
+    EditorToggle(String),
+    SaveScriptEditor,
+    CancelScriptEditor,
+    SetAnalysisTravellers(u8),
+    RunStatistics,
+    ContinueStatistics,
+    CloseStatistics,
+    SetBalanceTarget(bool),
+    SetBalanceMin(i32),
+    SetBalanceMax(i32),
+    ToggleTagMenu,
+    AddTagConstraint,
+    RemoveTagConstraint(usize),
+    SetConstraintTag(usize, String),
+    SetConstraintType(usize, Option<Type>),
+    SetConstraintMin(usize, Option<u8>),
+    SetConstraintMax(usize, Option<u8>),
+    UpdateTagCharacterInput(String),
+    UpdateTagInput(String),
+    AddUserTag,
+    RemoveUserTag(String, String),
+    SetScriptDistribution(String),
+    UpdateDistributionNameInput(String),
+    UpdateDistributionInput(String),
+    SaveDistribution,
+    DeleteDistribution(String),
+    DealSeats,
+    ClickSeat(usize),
+    SetLanguage(Language),
+    ShowCharacterSheet(SheetScope),
+    CloseCharacterSheet,
+    Print,
+    DownloadTokens,
+    /// icon URLs of the selected characters mapped to data URIs
+    TokenIconsLoaded(HashMap<String, String>),
+    ToggleReveal,
+    ShowQr(String),
+    CloseQr,
 }
 
 pub struct App {
     state: State,
+    /// file readers keyed by a counter, since files from different folders can share a name
+    readers: HashMap<usize, FileReader>,
+    next_reader: usize,
+    /// schedules the next chunk of statistics runs, dropping it cancels them
+    statistics_timeout: Option<Timeout>,
 }
 
 impl Component for App {
@@ -39,27 +103,28 @@ impl Component for App {
     type Properties = ();
 
     fn create(_ctx: &Context<Self>) -> Self {
+        crate::icons::set_favicon();
         let included_data = serde_json::from_str(include_str!("data.json")).unwrap();
         let user_data = LocalStorage::get(crate::consts::STORAGE_KEY).unwrap_or_default();
-        let mut state = State {
-            script: crate::consts::DEFAULT_SCRIPT.to_string(),
-            selected: BTreeMap::new(),
-            player_count: 10,
-            type_counts_locked: true,
-            outsider_count: 0,
-            minion_count: 2,
-            demon_count: 1,
-            included_data,
-            user_data,
-            expanded_script_menu: false,
-            script_rename_input: String::new(),
-            script_input: String::new(),
-        };
+        let mut state = State::new(included_data, user_data);
+        // Players opening their reveal link only get to see their own character
+        if let Ok(hash) = web_sys::window().unwrap().location().hash()
+            && let Some(token) = hash.strip_prefix("#reveal=")
+        {
+            state.reveal = Some(reveal::decode(token));
+        }
+        state.migrate_legacy_ids();
+        state.update_type_counts();
         state.randomize_unlocked();
-        Self { state }
+        Self {
+            state,
+            readers: HashMap::new(),
+            next_reader: 0,
+            statistics_timeout: None,
+        }
     }
 
-    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
         let redraw = match msg {
             Msg::Toggle(character) => {
                 if self.state.selected.contains_key(&character) {
@@ -97,21 +162,127 @@ impl Component for App {
                 self.state.update_type_counts();
                 true
             }
-            Msg::SetOutsiderCount(count) => {
-                self.state.outsider_count = count;
+            Msg::SetTypeCountMin(r#type, count) => {
+                self.state.set_type_count_min(r#type, count);
                 true
             }
-            Msg::SetMinionCount(count) => {
-                self.state.minion_count = count;
-                true
-            }
-            Msg::SetDemonCount(count) => {
-                self.state.demon_count = count;
+            Msg::SetTypeCountMax(r#type, count) => {
+                self.state.set_type_count_max(r#type, count);
                 true
             }
             Msg::SetScript(script) => {
                 self.state.script = script;
                 self.state.selected.clear();
+                self.state.update_type_counts();
+                true
+            }
+            Msg::SetScriptDistribution(table) => {
+                self.state.set_script_distribution(table);
+                true
+            }
+            Msg::UpdateDistributionNameInput(name) => {
+                self.state.distribution_name_input = name;
+                false
+            }
+            Msg::UpdateDistributionInput(text) => {
+                self.state.distribution_input = text;
+                false
+            }
+            Msg::SaveDistribution => {
+                self.state.save_distribution_table();
+                true
+            }
+            Msg::DeleteDistribution(name) => {
+                self.state
+                    .user_data
+                    .distributions
+                    .retain(|t| t.name != name);
+                self.state.update_type_counts();
+                true
+            }
+            Msg::ShowCharacterSheet(scope) => {
+                self.state.character_sheet = Some(scope);
+                true
+            }
+            Msg::CloseCharacterSheet => {
+                self.state.character_sheet = None;
+                true
+            }
+            Msg::DownloadTokens => {
+                let icons: BTreeSet<String> = self
+                    .state
+                    .selected_characters()
+                    .iter()
+                    .filter_map(|c| c.icon.clone())
+                    .collect();
+                // The icons are embedded so that the pages can be printed from anywhere
+                ctx.link().send_future(async move {
+                    let mut data_uris = HashMap::new();
+                    for icon in icons {
+                        if let Some(uri) = crate::icons::data_uri(&icon).await {
+                            data_uris.insert(icon, uri);
+                        }
+                    }
+                    Msg::TokenIconsLoaded(data_uris)
+                });
+                false
+            }
+            Msg::TokenIconsLoaded(data_uris) => {
+                let pages = token_pages(
+                    &self.state.selected_characters(),
+                    self.state.player_count,
+                    self.language(),
+                    |url| {
+                        data_uris
+                            .get(url)
+                            .cloned()
+                            .unwrap_or_else(|| url.to_string())
+                    },
+                );
+                match pages.as_slice() {
+                    [] => {}
+                    [page] => download("tokens.svg", "image/svg+xml", page.as_bytes()),
+                    pages => {
+                        let files: Vec<(String, Vec<u8>)> = pages
+                            .iter()
+                            .enumerate()
+                            .map(|(i, page)| {
+                                (format!("tokens-{}.svg", i + 1), page.clone().into_bytes())
+                            })
+                            .collect();
+                        download("tokens.zip", "application/zip", &zip(&files));
+                    }
+                }
+                false
+            }
+            Msg::Print => {
+                if let Err(e) = web_sys::window().unwrap().print() {
+                    tracing::error!(?e);
+                }
+                false
+            }
+            Msg::ShowQr(data) => {
+                self.state.qr = Some(data);
+                true
+            }
+            Msg::CloseQr => {
+                self.state.qr = None;
+                true
+            }
+            Msg::ToggleReveal => {
+                self.state.revealed = !self.state.revealed;
+                true
+            }
+            Msg::SetLanguage(language) => {
+                self.state.user_data.language = language;
+                true
+            }
+            Msg::DealSeats => {
+                self.state.deal_seats();
+                true
+            }
+            Msg::ClickSeat(seat) => {
+                self.state.click_seat(seat);
                 true
             }
             Msg::ClearAll => {
@@ -148,6 +319,8 @@ impl Component for App {
                     .retain(|s| s.name != self.state.script);
                 self.state.script = crate::consts::DEFAULT_SCRIPT.to_string();
                 self.state.expanded_script_menu = false;
+                self.state.selected.clear();
+                self.state.update_type_counts();
                 true
             }
             Msg::UpdateScriptInput(input) => {
@@ -156,24 +329,216 @@ impl Component for App {
             }
             Msg::ImportScript => {
                 self.state.import_script();
-                self.state.script_input.clear();
+                true
+            }
+            Msg::ImportScriptFiles(files) => {
+                for file in files {
+                    let id = self.next_reader;
+                    self.next_reader += 1;
+                    let file_name = file.name();
+                    let link = ctx.link().clone();
+                    let reader = gloo_file::callbacks::read_as_text(&file, move |result| {
+                        let result = result.map_err(|e| e.to_string());
+                        link.send_message(Msg::ScriptFileLoaded(id, file_name, result))
+                    });
+                    self.readers.insert(id, reader);
+                }
+                false
+            }
+            Msg::ScriptFileLoaded(id, file_name, result) => {
+                self.readers.remove(&id);
+                match result {
+                    Ok(json) => self.state.add_pending_import(file_name, &json),
+                    Err(message) => self.state.pending_imports.push(PendingImport {
+                        source: file_name,
+                        result: Err(ImportError::Read(message)),
+                    }),
+                }
+                true
+            }
+            Msg::ConfirmImport(index) => {
+                self.state.confirm_import(index);
+                true
+            }
+            Msg::DiscardImport(index) => {
+                if index < self.state.pending_imports.len() {
+                    self.state.pending_imports.remove(index);
+                }
+                true
+            }
+            Msg::NewScript => {
+                self.state.new_script();
+                self.state.expanded_script_menu = false;
+                true
+            }
+            Msg::CloneScript => {
+                self.state.clone_current_script();
+                self.state.expanded_script_menu = false;
+                true
+            }
+            Msg::EditScript => {
+                self.state.edit_current_script();
                 self.state.expanded_script_menu = false;
                 true
             }
+            Msg::UpdateEditorName(name) => {
+                if let Some(editor) = &mut self.state.script_editor {
+                    editor.script.name = name;
+                }
+                false
+            }
+            Msg::UpdateEditorSearch(search) => {
+                if let Some(editor) = &mut self.state.script_editor {
+                    editor.search = search;
+                }
+                true
+            }
+            Msg::EditorToggle(character) => {
+                if let Some(editor) = &mut self.state.script_editor {
+                    editor.toggle(character);
+                }
+                true
+            }
+            Msg::SaveScriptEditor => {
+                self.state.save_script_editor();
+                true
+            }
+            Msg::CancelScriptEditor => {
+                self.state.script_editor = None;
+                true
+            }
+            Msg::SetAnalysisTravellers(count) => {
+                self.state.set_analysis_travellers(count);
+                true
+            }
+            Msg::RunStatistics => {
+                self.state.statistics = Some(Statistics::new(
+                    &self.state.script_characters(),
+                    crate::consts::STATISTICS_RUNS,
+                ));
+                self.schedule_statistics(ctx.link());
+                true
+            }
+            Msg::ContinueStatistics => {
+                self.statistics_timeout = None;
+                let Some(mut statistics) = self.state.statistics.take() else {
+                    return false;
+                };
+                simulate(
+                    &self.state,
+                    &mut statistics,
+                    crate::consts::STATISTICS_CHUNK_RUNS,
+                );
+                if !statistics.is_done() {
+                    self.schedule_statistics(ctx.link());
+                }
+                self.state.statistics = Some(statistics);
+                true
+            }
+            Msg::CloseStatistics => {
+                self.statistics_timeout = None;
+                self.state.statistics = None;
+                true
+            }
+            Msg::SetBalanceTarget(value) => {
+                self.state.balance_target = value.then_some(crate::consts::DEFAULT_BALANCE_TARGET);
+                true
+            }
+            Msg::ToggleTagMenu => {
+                self.state.expanded_tag_menu = !self.state.expanded_tag_menu;
+                true
+            }
+            Msg::AddTagConstraint => {
+                self.state
+                    .user_data
+                    .tag_constraints
+                    .push(Default::default());
+                true
+            }
+            Msg::RemoveTagConstraint(index) => {
+                if index < self.state.user_data.tag_constraints.len() {
+                    self.state.user_data.tag_constraints.remove(index);
+                }
+                true
+            }
+            Msg::SetConstraintTag(index, tag) => {
+                if let Some(constraint) = self.state.user_data.tag_constraints.get_mut(index) {
+                    constraint.tag = tag;
+                }
+                true
+            }
+            Msg::SetConstraintType(index, r#type) => {
+                if let Some(constraint) = self.state.user_data.tag_constraints.get_mut(index) {
+                    constraint.r#type = r#type;
+                }
+                true
+            }
+            Msg::SetConstraintMin(index, min) => {
+                if let Some(constraint) = self.state.user_data.tag_constraints.get_mut(index) {
+                    constraint.min = min;
+                }
+                true
+            }
+            Msg::SetConstraintMax(index, max) => {
+                if let Some(constraint) = self.state.user_data.tag_constraints.get_mut(index) {
+                    constraint.max = max;
+                }
+                true
+            }
+            Msg::UpdateTagCharacterInput(name) => {
+                self.state.tag_character_input = name;
+                false
+            }
+            Msg::UpdateTagInput(tag) => {
+                self.state.tag_input = tag;
+                false
+            }
+            Msg::AddUserTag => {
+                self.state.add_user_tag();
+                true
+            }
+            Msg::RemoveUserTag(id, tag) => {
+                self.state.remove_user_tag(&id, &tag);
+                true
+            }
+            Msg::SetBalanceMin(min) => {
+                if let Some(target) = &mut self.state.balance_target {
+                    *target = min..=(*target.end()).max(min);
+                }
+                true
+            }
+            Msg::SetBalanceMax(max) => {
+                if let Some(target) = &mut self.state.balance_target {
+                    *target = (*target.start()).min(max)..=max;
+                }
+                true
+            }
         };
+        self.state.refresh_script_checks();
         LocalStorage::set(crate::consts::STORAGE_KEY, &self.state.user_data).unwrap();
         redraw
     }
 
     fn view(&self, ctx: &Context<Self>) -> Html {
+        if let Some(reveal) = &self.state.reveal {
+            return self.view_reveal(ctx.link(), reveal);
+        }
         let set_type_counts_locked = ctx.link().callback(|e: Event| {
             Msg::SetTypeCountsLocked(e.target_unchecked_into::<HtmlInputElement>().checked())
         });
+        let set_balance_target = ctx.link().callback(|e: Event| {
+            Msg::SetBalanceTarget(e.target_unchecked_into::<HtmlInputElement>().checked())
+        });
+        let balance_target = self.state.balance_target.clone();
+        let player_counts = self.state.player_count_range();
 
         html! {
             <main>
                 <div class="sidebar">
                     <div class="box">
+                        <div class="row">
+                            {self.view_language_dropdown(ctx.link())}
+                        </div>
                         <div class="row">
                             {self.view_script_dropdown(ctx.link())}
                             <button onclick={ctx.link().callback(|_| Msg::ToggleScriptMenu)}>{"⚙️"}</button>
@@ -182,79 +547,188 @@ impl Component for App {
                     {self.view_script_menu(ctx.link())}
                     <div class="box">
                         <div class="row">
-                            <label>{"Player Count: "}</label>
-                            <input type="number" min="1" max="50"
+                            <label>{self.t("Player Count: ")}</label>
+                            <input type="number"
+                                min={player_counts.start().to_string()}
+                                max={player_counts.end().to_string()}
                                 value={self.state.player_count.to_string()}
-                                onchange={clamped(ctx, 5, 15, Msg::SetPlayerCount)}
+                                onchange={clamped(ctx, *player_counts.start(), *player_counts.end(), Msg::SetPlayerCount)}
                             />
                         </div>
+                        if self.state.missing_distribution_row {
+                            <p>{"⚠️ "}{self.t("The distribution table has no row for this player count, set the type counts manually")}</p>
+                        }
                         <div class="row">
-                            <label>{"Default Type Counts: "}</label>
+                            <label>{self.t("Default Type Counts: ")}</label>
                             <input type="checkbox"
                                 checked={self.state.type_counts_locked}
                                 onchange={set_type_counts_locked}
                             />
                         </div>
                         <div class="row">
-                            <label>{format!("{}: ", Type::Townsfolk.plural_str())}</label>
-                            <input type="number" min="0" max="50"
+                            <label>{format!("{}: ", self.t(Type::Townsfolk.plural_str()))}</label>
+                            <input type="number" min="0" max={crate::consts::MAX_TYPE_COUNT.to_string()}
                                 disabled=true
-                                value={self.state.townsfolk_count().to_string()}
+                                value={self.state.townsfolk_count().start().to_string()}
                             />
-                        </div>
-                        <div class="row">
-                            <label>{format!("{}: ", Type::Outsider.plural_str())}</label>
-                            <input type="number" min="0" max="50"
-                                disabled={self.state.type_counts_locked}
-                                value={self.state.outsider_count.to_string()}
-                                onchange={clamped(ctx, 0, 15, Msg::SetOutsiderCount)}
+                            {"–"}
+                            <input type="number" min="0" max={crate::consts::MAX_TYPE_COUNT.to_string()}
+                                disabled=true
+                                value={self.state.townsfolk_count().end().to_string()}
                             />
                         </div>
+                        {self.view_type_count(ctx, Type::Outsider)}
+                        {self.view_type_count(ctx, Type::Minion)}
+                        {self.view_type_count(ctx, Type::Demon)}
+                    </div>
+                    <div class="box">
                         <div class="row">
-                            <label>{format!("{}: ", Type::Minion.plural_str())}</label>
-                            <input type="number" min="0" max="50"
-                                disabled={self.state.type_counts_locked}
-                                value={self.state.minion_count.to_string()}
-                                onchange={clamped(ctx, 0, 15, Msg::SetMinionCount)}
+                            <label>{self.t("Target Balance: ")}</label>
+                            <input type="checkbox"
+                                checked={balance_target.is_some()}
+                                onchange={set_balance_target}
                             />
                         </div>
+                        if let Some(target) = balance_target {
+                            <div class="row">
+                                <input type="number" min="-20" max="20"
+                                    value={target.start().to_string()}
+                                    onchange={clamped(ctx, -20, 20, Msg::SetBalanceMin)}
+                                />
+                                {self.t(" to ")}
+                                <input type="number" min="-20" max="20"
+                                    value={target.end().to_string()}
+                                    onchange={clamped(ctx, -20, 20, Msg::SetBalanceMax)}
+                                />
+                            </div>
+                        }
+                    </div>
+                    <div class="box">
                         <div class="row">
-                            <label>{format!("{}: ", Type::Demon.plural_str())}</label>
-                            <input type="number" min="0" max="50"
-                                disabled={self.state.type_counts_locked}
-                                value={self.state.demon_count.to_string()}
-                                onchange={clamped(ctx, 0, 15, Msg::SetDemonCount)}
-                            />
+                            <label>{self.t("Tag Constraints: ")}</label>
+                            <button onclick={ctx.link().callback(|_| Msg::ToggleTagMenu)}>{"🏷️"}</button>
                         </div>
                     </div>
+                    {self.view_tag_menu(ctx.link())}
                     <div class="box">
                         <div class="row">
-                            {"Valid List: "}{if self.state.is_valid_character_list() {"✅"} else {"❌"}}
+                            {self.t("Valid List: ")}{if self.state.is_valid_character_list() {"✅"} else {"❌"}}
                         </div>
                     </div>
                     <div class="box">
                         <div class="row">
-                            <button onclick={ctx.link().callback(|_| Msg::Randomize)}>{"Randomize Unlocked"}</button>
+                            <button onclick={ctx.link().callback(|_| Msg::Randomize)}>{self.t("Randomize Unlocked")}</button>
                         </div>
                         <div class="row">
-                            <button onclick={ctx.link().callback(|_| Msg::SetLockForAll(true))}>{"Lock All"}</button>
-                            <button onclick={ctx.link().callback(|_| Msg::SetLockForAll(false))}>{"Unlock All"}</button>
-                            <button onclick={ctx.link().callback(|_| Msg::ClearAll)}>{"Clear All"}</button>
+                            <button onclick={ctx.link().callback(|_| Msg::SetLockForAll(true))}>{self.t("Lock All")}</button>
+                            <button onclick={ctx.link().callback(|_| Msg::SetLockForAll(false))}>{self.t("Unlock All")}</button>
+                            <button onclick={ctx.link().callback(|_| Msg::ClearAll)}>{self.t("Clear All")}</button>
+                        </div>
+                        <div class="row">
+                            <button onclick={ctx.link().callback(|_| Msg::RunStatistics)}>{self.t("Generator Statistics")}</button>
+                            <button onclick={ctx.link().callback(|_| Msg::ShowCharacterSheet(SheetScope::Script))}>{self.t("Character Sheet")}</button>
+                            {self.view_token_download(ctx.link())}
                         </div>
                     </div>
                     {self.view_character_list(ctx.link())}
                 </div>
                 <div class="content">
                     <div class="box">
-                        {self.view_selected_characters(ctx.link())}
+                        if self.state.script_editor.is_some() {
+                            {self.view_script_editor(ctx.link())}
+                        } else if let Some(statistics) = &self.state.statistics {
+                            {self.view_statistics(ctx.link(), statistics)}
+                        } else if let Some(scope) = self.state.character_sheet {
+                            {self.view_character_sheet(ctx.link(), scope)}
+                        } else {
+                            {self.view_script_header()}
+                            {self.view_balance()}
+                            {self.view_variables()}
+                            {self.view_selected_characters(ctx.link())}
+                            {self.view_setup_notes()}
+                            {self.view_seating(ctx.link())}
+                        }
                     </div>
                 </div>
+                {self.view_qr(ctx.link())}
             </main>
         }
     }
 }
 
 impl App {
+    /// Runs the next chunk of statistics after the browser had a chance to redraw.
+    fn schedule_statistics(&mut self, link: &Scope<Self>) {
+        let link = link.clone();
+        self.statistics_timeout = Some(Timeout::new(0, move || {
+            link.send_message(Msg::ContinueStatistics)
+        }));
+    }
+
+    fn language(&self) -> Language {
+        self.state.user_data.language
+    }
+
+    fn t(&self, text: &'static str) -> &'static str {
+        self.language().t(text)
+    }
+
+    fn tf(&self, text: &'static str, args: &[(&str, &dyn std::fmt::Display)]) -> String {
+        self.language().tf(text, args)
+    }
+
+    /// The translated name of the character with the id, or the id if it is unknown.
+    fn character_name(&self, id: &str) -> String {
+        self.state
+            .get_character(id)
+            .map_or(id, |c| self.language().name(c))
+            .to_string()
+    }
+
+    fn view_language_dropdown(&self, link: &Scope<Self>) -> Html {
+        let options = Language::ALL.map(|l| {
+            html! {
+                <option selected={l == self.language()} value={l.code()}>{l.native_name()}</option>
+            }
+        });
+        let set_language = link.callback(|e: Event| {
+            Msg::SetLanguage(
+                Language::from_code(&get_text(e.target().unwrap())).unwrap_or_default(),
+            )
+        });
+        html! {
+            <>
+            <label for="language">{self.t("Language: ")}</label>
+            <select name="language" id="language" onchange={set_language}>{ for options }</select>
+            if self.state.script_characters().iter().any(|&c| !self.language().translates(c)) {
+                <p>{self.t("Some characters are not translated yet and are shown in English")}</p>
+            }
+            </>
+        }
+    }
+
+    fn view_type_count(&self, ctx: &Context<Self>, r#type: Type) -> Html {
+        let Some(count) = self.state.type_count(r#type) else {
+            return html! {};
+        };
+        html! {
+            <div class="row">
+                <label>{format!("{}: ", self.t(r#type.plural_str()))}</label>
+                <input type="number" min="0" max={crate::consts::MAX_TYPE_COUNT.to_string()}
+                    disabled={self.state.type_counts_locked}
+                    value={count.start().to_string()}
+                    onchange={clamped(ctx, 0, crate::consts::MAX_TYPE_COUNT, move |c| Msg::SetTypeCountMin(r#type, c))}
+                />
+                {"–"}
+                <input type="number" min="0" max={crate::consts::MAX_TYPE_COUNT.to_string()}
+                    disabled={self.state.type_counts_locked}
+                    value={count.end().to_string()}
+                    onchange={clamped(ctx, 0, crate::consts::MAX_TYPE_COUNT, move |c| Msg::SetTypeCountMax(r#type, c))}
+                />
+            </div>
+        }
+    }
+
     fn view_character_list(&self, link: &Scope<Self>) -> Html {
         let by_type = group_characters_by_type(&self.state.script_characters());
         let mut li = Vec::new();
@@ -278,24 +752,29 @@ impl App {
         html! {
             <li class="clickable">
                 <div class={classes!(selected)} onclick={onclick}>
-                    <img src={char.icon.clone()} width="32.5" height="32.5"/>
-                    {&char.name}
+                    {view_icon(char.icon.as_deref(), Some("32.5"))}
+                    {self.language().name(char)}
                 </div>
             </li>
         }
     }
 
     fn view_selected_characters(&self, link: &Scope<Self>) -> Html {
-        let by_type = group_characters_by_type(&self.state.selected_characters());
+        let characters = self.state.selected_characters();
+        let undealt = undealt_types(&characters);
+        let by_type = group_characters_by_type(&characters);
         let mut lists = Vec::new();
         for (r#type, cs) in by_type {
-            let li = cs.iter().map(|c| self.view_selected_character(link, c));
+            let dealt = !undealt.contains(&r#type);
+            let li = cs
+                .iter()
+                .map(|c| self.view_selected_character(link, c, dealt));
             lists.push(html! { <> {self.view_type(&r#type)} <ul> { for li } </ul> </> });
         }
         html! { { for lists } }
     }
 
-    fn view_selected_character(&self, link: &Scope<Self>, char: &Character) -> Html {
+    fn view_selected_character(&self, link: &Scope<Self>, char: &Character, dealt: bool) -> Html {
         let toggle_lock = {
             let id = char.id();
             link.callback(move |_| Msg::ToggleLock(id.clone()))
@@ -308,24 +787,443 @@ impl App {
             .unwrap_or(Default::default());
         html! {
             <li class={classes!("clickable", if selected.locked {"locked"} else {"unlocked"})} onclick={toggle_lock}>
-                <img src={char.icon.clone()}/>
+                {view_icon(char.icon.as_deref(), None)}
                 <div>
-                    <h4>{&char.name}</h4>
-                    <p>{&char.description}</p>
+                    <h4>
+                        {self.language().name(char)}
+                        if !dealt {
+                            <span class="undealt">{self.t(" (not in the bag)")}</span>
+                        }
+                    </h4>
+                    <p>{self.language().description(char)}</p>
                 </div>
             </li>
         }
     }
 
+    fn view_setup_notes(&self) -> Html {
+        let notes = self.state.setup_notes();
+        if notes.is_empty() {
+            return html! {};
+        }
+        let notes = notes.into_iter().map(|n| {
+            html! { <li><b>{n.character}{": "}</b>{n.note}</li> }
+        });
+        html! {
+            <div class="setup-notes">
+                <h3>{self.t("Setup Notes")}</h3>
+                <ul>{ for notes }</ul>
+            </div>
+        }
+    }
+
+    fn view_seating(&self, link: &Scope<Self>) -> Html {
+        if self.state.selected.is_empty() {
+            return html! {};
+        }
+        let deal = html! {
+            <button onclick={link.callback(|_| Msg::DealSeats)}>{self.t("Deal Seats")}</button>
+        };
+        let Some(seats) = self.state.seats() else {
+            return html! { <div class="seating"><h3>{self.t("Seating")}</h3>{deal}</div> };
+        };
+        let violations = seating::violations(&seats);
+        let seats = seats.iter().enumerate().map(|(i, c)| {
+            let selected = self.state.selected_seat == Some(i);
+            html! {
+                <li class={classes!("clickable", selected.then_some("selected"))}
+                    onclick={link.callback(move |_| Msg::ClickSeat(i))}>
+                    {view_icon(c.icon.as_deref(), None)}
+                    {self.language().name(c)}
+                </li>
+            }
+        });
+        html! {
+            <div class="seating">
+                <h3>{self.t("Seating")}</h3>
+                {deal}
+                <ol>{ for seats }</ol>
+                if violations.is_empty() {
+                    <p>{self.t("✅ All seating conditions are met")}</p>
+                } else {
+                    <ul class="violations">
+                        { for violations.iter().map(|v| html! { <li>{"❌ "}{v.describe(self.language(), |id| self.character_name(id))}</li> }) }
+                    </ul>
+                }
+                {self.view_reveal_links(link)}
+            </div>
+        }
+    }
+
+    fn view_reveal_links(&self, link: &Scope<Self>) -> Html {
+        let location = web_sys::window().unwrap().location();
+        let base_url = format!(
+            "{}{}",
+            location.origin().unwrap_or_default(),
+            location.pathname().unwrap_or_default()
+        );
+        let Some(links) = self.state.reveal_links(&base_url) else {
+            return html! {};
+        };
+        let bluffs: Vec<&str> = self
+            .state
+            .bluffs
+            .iter()
+            .filter_map(|id| self.state.get_character(id))
+            .map(|c| self.language().name(c))
+            .collect();
+        html! {
+            <div class="reveal-links">
+                <h3>{self.t("Reveal Links")}</h3>
+                <p>{self.t("Send each player the link for their seat, it only shows their own character.")}</p>
+                <ol>
+                    { for links.into_iter().map(|url| html! {
+                        <li>
+                            <a href={url.clone()} target="_blank">{self.t("Reveal")}</a>
+                            <button onclick={link.callback(move |_| Msg::ShowQr(url.clone()))}>{self.t("QR")}</button>
+                        </li>
+                    }) }
+                </ol>
+                if !bluffs.is_empty() {
+                    <p>{self.t("Demon bluffs")}{": "}{bluffs.join(", ")}</p>
+                }
+            </div>
+        }
+    }
+
+    /// Modal with the QR code of the shown payload, clicking anywhere closes it.
+    fn view_qr(&self, link: &Scope<Self>) -> Html {
+        let Some(data) = &self.state.qr else {
+            return html! {};
+        };
+        let content = match qr_svg(data) {
+            Ok(svg) => {
+                let src = format!(
+                    "data:image/svg+xml;charset=utf-8,{}",
+                    js_sys::encode_uri_component(&svg)
+                );
+                html! { <img src={src} alt={data.clone()}/> }
+            }
+            Err(e) => html! { <p>{self.t("Too much data for a QR code")}{": "}{e}</p> },
+        };
+        html! {
+            <div class="qr-modal" onclick={link.callback(|_| Msg::CloseQr)}>
+                <div class="box">
+                    {content}
+                    <p>{self.t("Click anywhere to close")}</p>
+                </div>
+            </div>
+        }
+    }
+
+    fn view_reveal(&self, link: &Scope<Self>, reveal: &Result<Reveal, String>) -> Html {
+        let content = match reveal {
+            Err(e) => html! {
+                <p>{self.t("This reveal link is invalid")}{": "}{e}</p>
+            },
+            Ok(_) if !self.state.revealed => html! {
+                <button class="reveal-button" onclick={link.callback(|_| Msg::ToggleReveal)}>
+                    {self.t("Tap to reveal your character")}
+                </button>
+            },
+            Ok(reveal) => {
+                let character = self.state.get_character(&reveal.character);
+                let names = |seats: &[u8]| -> String {
+                    seats
+                        .iter()
+                        .map(u8::to_string)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                };
+                html! {
+                    <div onclick={link.callback(|_| Msg::ToggleReveal)}>
+                        <p>{self.t("Seat")}{" "}{reveal.seat}</p>
+                        if let Some(c) = character {
+                            {view_icon(c.icon.as_deref(), Some("128"))}
+                            <h2>{self.language().name(c)}</h2>
+                            <p>{self.language().description(c)}</p>
+                        } else {
+                            <h2>{&reveal.name}</h2>
+                        }
+                        if let Some(evil) = &reveal.evil {
+                            if !evil.demons.is_empty() {
+                                <p>{self.t("Demon seats")}{": "}{names(&evil.demons)}</p>
+                            }
+                            if !evil.minions.is_empty() {
+                                <p>{self.t("Minion seats")}{": "}{names(&evil.minions)}</p>
+                            }
+                            if !evil.disguised.is_empty() {
+                                <p>{self.t("Seats that think they are evil")}{": "}{names(&evil.disguised)}</p>
+                            }
+                            if !evil.bluffs.is_empty() {
+                                <p>
+                                    {self.t("Demon bluffs")}{": "}
+                                    {evil.bluffs.iter().map(|id| {
+                                        self.state.get_character(id).map_or(id.as_str(), |c| self.language().name(c))
+                                    }).collect::<Vec<_>>().join(", ")}
+                                </p>
+                            }
+                        }
+                        <p class="hint">{self.t("Tap to hide")}</p>
+                    </div>
+                }
+            }
+        };
+        html! {
+            <main class="reveal">
+                <div class="box">{content}</div>
+            </main>
+        }
+    }
+
     fn view_type(&self, r#type: &Type) -> Html {
         html! {
             <h3>
-                {r#type.plural_str()}
-                <img src={r#type.icon().to_string()} width="52" height="52"/>
+                {self.t(r#type.plural_str())}
+                {view_icon(Some(r#type.icon()), Some("52"))}
             </h3>
         }
     }
 
+    fn view_script_header(&self) -> Html {
+        let Some(script) = self.state.get_current_script_data() else {
+            return html! {};
+        };
+        let bootlegger = script
+            .bootlegger
+            .iter()
+            .map(|rule| html! { <li>{rule}</li> });
+        html! {
+            <div class="script-header">
+                if let Some(logo) = &script.logo {
+                    <img src={logo.clone()} alt={script.name.clone()}/>
+                }
+                if !script.hide_title {
+                    <h2>{&script.name}</h2>
+                }
+                if let Some(author) = &script.author {
+                    <p>{format!("{} {author}", self.t("by"))}</p>
+                }
+                if !script.bootlegger.is_empty() {
+                    <h4>{self.t("Bootlegger Rules")}</h4>
+                    <ul class="bootlegger">{ for bootlegger }</ul>
+                }
+            </div>
+        }
+    }
+
+    fn view_script_editor(&self, link: &Scope<Self>) -> Html {
+        let Some(editor) = &self.state.script_editor else {
+            return html! {};
+        };
+        let update_name =
+            link.callback(|e: InputEvent| Msg::UpdateEditorName(get_text(e.target().unwrap())));
+        let update_search =
+            link.callback(|e: InputEvent| Msg::UpdateEditorSearch(get_text(e.target().unwrap())));
+
+        // Characters that only differ by a number share an id in scripts
+        let mut catalogue: Vec<&Character> = Vec::new();
+        for c in self.state.characters() {
+            if !catalogue
+                .iter()
+                .any(|o| o.id_no_numeric() == c.id_no_numeric())
+            {
+                catalogue.push(c);
+            }
+        }
+        let catalogue: Vec<&Character> = catalogue
+            .into_iter()
+            .filter(|c| editor.matches_search(c, self.language()))
+            .collect();
+
+        let mut lists = Vec::new();
+        for (r#type, cs) in group_characters_by_type(&catalogue) {
+            let li = cs.iter().map(|&c| {
+                let toggle = {
+                    let id = c.id_no_numeric();
+                    link.callback(move |_| Msg::EditorToggle(id.clone()))
+                };
+                let included = if editor.contains(c) {
+                    "locked"
+                } else {
+                    "unlocked"
+                };
+                html! {
+                    <li class={classes!("clickable", included)} onclick={toggle}>
+                        {view_icon(c.icon.as_deref(), None)}
+                        <div>
+                            <h4>{self.language().name(c)}</h4>
+                            <p>{self.language().description(c)}</p>
+                        </div>
+                    </li>
+                }
+            });
+            lists.push(html! { <> {self.view_type(&r#type)} <ul> { for li } </ul> </> });
+        }
+
+        html! {
+            <>
+                <div class="editor-bar">
+                    <input type="text" placeholder={self.t("Script name...")}
+                        oninput={update_name}
+                        value={editor.script.name.clone()}
+                    />
+                    <input type="search" placeholder={self.t("Search characters...")}
+                        oninput={update_search}
+                        value={editor.search.clone()}
+                    />
+                    <span>{self.tf("{count} characters", &[("count", &editor.script.characters.len())])}</span>
+                    <button onclick={link.callback(|_| Msg::SaveScriptEditor)}>{self.t("Save")}</button>
+                    <button onclick={link.callback(|_| Msg::CancelScriptEditor)}>{self.t("Cancel")}</button>
+                </div>
+                { for lists }
+            </>
+        }
+    }
+
+    fn view_balance(&self) -> Html {
+        let balance = self.state.balance();
+        let categories = Category::ALL.map(|category| {
+            let count = balance.categories.get(&category).copied().unwrap_or(0);
+            html! { <span>{format!("{}: {count}", self.t(category.str()))}</span> }
+        });
+        html! {
+            <div class="balance">
+                <h4>{self.tf(
+                    "Balance: {score} ({good} good, {evil} evil)",
+                    &[
+                        ("score", &format!("{:+}", balance.score())),
+                        ("good", &balance.good),
+                        ("evil", &balance.evil),
+                    ],
+                )}</h4>
+                <p>{ for categories }</p>
+            </div>
+        }
+    }
+
+    fn view_variables(&self) -> Html {
+        let variables = self.state.variables();
+        if variables.is_empty() {
+            return html! {};
+        }
+        let variables = variables.into_iter().map(|(c, variable, value)| {
+            html! { <span>{format!("{}: {variable} = {value}", self.language().name(c))}</span> }
+        });
+        html! { <p class="variables">{ for variables }</p> }
+    }
+
+    fn view_character_sheet(&self, link: &Scope<Self>, scope: SheetScope) -> Html {
+        let language = self.language();
+        let script = self.state.get_current_script_data();
+        let characters = self.state.sheet_characters(scope);
+        let groups = group_characters_by_type(&characters)
+            .into_iter()
+            .map(|(r#type, cs)| {
+                let li = cs.into_iter().map(|c| {
+                    html! {
+                        <li>
+                            {view_icon(c.icon.as_deref(), None)}
+                            <div>
+                                <h4>{language.name(c)}</h4>
+                                <p>{language.description(c)}</p>
+                            </div>
+                        </li>
+                    }
+                });
+                html! { <> <h3>{self.t(r#type.plural_str())}</h3> <ul>{ for li }</ul> </> }
+            });
+        let toggle = match scope {
+            SheetScope::Script => html! {
+                <button onclick={link.callback(|_| Msg::ShowCharacterSheet(SheetScope::Selected))}>
+                    {self.t("Selected Characters Only")}
+                </button>
+            },
+            SheetScope::Selected => html! {
+                <button onclick={link.callback(|_| Msg::ShowCharacterSheet(SheetScope::Script))}>
+                    {self.t("Whole Script")}
+                </button>
+            },
+        };
+        html! {
+            <div class="character-sheet">
+                <div class="editor-bar no-print">
+                    {toggle}
+                    <button onclick={link.callback(|_| Msg::Print)}>{self.t("Print")}</button>
+                    <button onclick={link.callback(|_| Msg::CloseCharacterSheet)}>{self.t("Close")}</button>
+                </div>
+                <h2>{&self.state.script}</h2>
+                if let Some(author) = script.and_then(|s| s.author.as_ref()) {
+                    <p class="author">{format!("{} {author}", self.t("by"))}</p>
+                }
+                { for groups }
+            </div>
+        }
+    }
+
+    fn view_statistics(&self, link: &Scope<Self>, statistics: &Statistics) -> Html {
+        let successes = statistics.successes().max(1) as f64;
+        let percent = |n: usize| format!("{:.1}%", n as f64 * 100.0 / successes);
+
+        let characters = self.state.script_characters();
+        let mut characters: Vec<_> = characters
+            .iter()
+            .filter_map(|c| Some((*c, *statistics.characters.get(&c.id())?)))
+            .collect();
+        characters.sort_by_key(|&(c, n)| (std::cmp::Reverse(n), c));
+        let character_rows = characters.into_iter().map(|(c, n)| {
+            html! {
+                <tr>
+                    <td>
+                        {view_icon(c.icon.as_deref(), Some("32.5"))}
+                        {self.language().name(c)}
+                        if c.conditions.is_some() {
+                            {" 🍃"}
+                        }
+                    </td>
+                    <td>
+                        <div class="bar" style={format!("width: {}", percent(n))}/>
+                    </td>
+                    <td>{percent(n)}</td>
+                </tr>
+            }
+        });
+
+        let histograms = PLAYER_TYPES.map(|t| {
+            let histogram = statistics
+                .type_histogram(t)
+                .into_iter()
+                .map(|(count, n)| format!("{count}: {}", percent(n)))
+                .collect::<Vec<_>>()
+                .join(", ");
+            html! { <p><b>{format!("{}: ", self.t(t.plural_str()))}</b>{histogram}</p> }
+        });
+
+        html! {
+            <div class="statistics">
+                <div class="editor-bar">
+                    <span>{self.tf(
+                        "{lists} lists generated for {players} players ({failed} failed)",
+                        &[
+                            ("lists", &statistics.successes()),
+                            ("players", &self.state.player_count),
+                            ("failed", &statistics.failures),
+                        ],
+                    )}</span>
+                    if !statistics.is_done() {
+                        <progress value={statistics.runs.to_string()} max={statistics.total_runs.to_string()}/>
+                    }
+                    <button onclick={link.callback(|_| Msg::RunStatistics)}>{self.t("Run Again")}</button>
+                    <button onclick={link.callback(|_| Msg::CloseStatistics)}>{self.t("Close")}</button>
+                </div>
+                <h3>{self.t("Type Counts")}</h3>
+                { for histograms }
+                <h3>{self.t("Characters")}</h3>
+                <table>{ for character_rows }</table>
+            </div>
+        }
+    }
+
     fn view_script_dropdown(&self, link: &Scope<Self>) -> Html {
         let mut options = Vec::new();
         for script in self.state.scripts() {
@@ -342,12 +1240,120 @@ impl App {
             link.callback(move |e: Event| Msg::SetScript(get_text(e.target().unwrap())));
         html! {
             <>
-            <label for="script">{"Script: "}</label>
+            <label for="script">{self.t("Script: ")}</label>
             <select name="script" id="script" onchange={set_script}>{ for options }</select>
             </>
         }
     }
 
+    fn view_tag_menu(&self, link: &Scope<Self>) -> Html {
+        if !self.state.expanded_tag_menu {
+            return html! {};
+        }
+        let constraints = self
+            .state
+            .user_data
+            .tag_constraints
+            .iter()
+            .enumerate()
+            .map(|(i, constraint)| {
+                let set_min = link.callback(move |e: Event| {
+                    Msg::SetConstraintMin(i, parse_type_count(&get_text(e.target().unwrap())))
+                });
+                let set_max = link.callback(move |e: Event| {
+                    Msg::SetConstraintMax(i, parse_type_count(&get_text(e.target().unwrap())))
+                });
+                let set_type = link.callback(move |e: Event| {
+                    let text = get_text(e.target().unwrap());
+                    let r#type = PLAYER_TYPES.into_iter().find(|t| t.plural_str() == text);
+                    Msg::SetConstraintType(i, r#type)
+                });
+                let set_tag = link.callback(move |e: Event| {
+                    Msg::SetConstraintTag(i, get_text(e.target().unwrap()))
+                });
+                let types = PLAYER_TYPES.map(|t| {
+                    html! {
+                        <option selected={constraint.r#type == Some(t)} value={t.plural_str().to_string()}>
+                            {self.t(t.plural_str())}
+                        </option>
+                    }
+                });
+                let satisfied = constraint
+                    .is_satisfied(&self.state.selected_characters(), &self.state.user_data.tags);
+                html! {
+                    <div class="row constraint">
+                        <input type="number" min="0" max={crate::consts::MAX_TYPE_COUNT.to_string()} placeholder={self.t("min")}
+                            value={constraint.min.map(|n| n.to_string()).unwrap_or_default()}
+                            onchange={set_min}
+                        />
+                        {"–"}
+                        <input type="number" min="0" max={crate::consts::MAX_TYPE_COUNT.to_string()} placeholder={self.t("max")}
+                            value={constraint.max.map(|n| n.to_string()).unwrap_or_default()}
+                            onchange={set_max}
+                        />
+                        <input type="text" placeholder={self.t("tag")}
+                            value={constraint.tag.clone()}
+                            onchange={set_tag}
+                        />
+                        <select onchange={set_type}>
+                            <option selected={constraint.r#type.is_none()} value="">{self.t("Any")}</option>
+                            { for types }
+                        </select>
+                        {if satisfied {"✅"} else {"❌"}}
+                        <button onclick={link.callback(move |_| Msg::RemoveTagConstraint(i))}>{"✖"}</button>
+                    </div>
+                }
+            });
+
+        let update_tag_character_input = link
+            .callback(|e: InputEvent| Msg::UpdateTagCharacterInput(get_text(e.target().unwrap())));
+        let update_tag_input =
+            link.callback(|e: InputEvent| Msg::UpdateTagInput(get_text(e.target().unwrap())));
+        let character_names = self
+            .state
+            .characters()
+            .map(|c| html! { <option value={self.language().name(c).to_string()}/> });
+        let user_tags = self.state.user_data.tags.iter().flat_map(|(id, tags)| {
+            let name = self.character_name(id);
+            tags.iter().map(move |tag| {
+                let remove = {
+                    let (id, tag) = (id.clone(), tag.clone());
+                    link.callback(move |_| Msg::RemoveUserTag(id.clone(), tag.clone()))
+                };
+                html! {
+                    <div class="row">
+                        {format!("{name}: {tag}")}
+                        <button onclick={remove}>{"✖"}</button>
+                    </div>
+                }
+            })
+        });
+
+        html! {<>
+            <div class="box">
+                <p>{self.t("Amount of characters with a tag or category in generated lists:")}</p>
+                { for constraints }
+                <button onclick={link.callback(|_| Msg::AddTagConstraint)}>{self.t("Add Constraint")}</button>
+            </div>
+            <div class="box">
+                <p>{self.t("Your own character tags:")}</p>
+                <div class="row">
+                    <input type="text" list="character-names" placeholder={self.t("Character...")}
+                        oninput={update_tag_character_input}
+                        value={self.state.tag_character_input.clone()}
+                    />
+                    <datalist id="character-names">{ for character_names }</datalist>
+                    <input type="text" placeholder={self.t("Tag...")}
+                        oninput={update_tag_input}
+                        value={self.state.tag_input.clone()}
+                    />
+                    <button onclick={link.callback(|_| Msg::AddUserTag)}>{self.t("Add")}</button>
+                </div>
+                { for user_tags }
+            </div>
+        </>}
+    }
+
     fn view_script_menu(&self, link: &Scope<Self>) -> Html {
         if !self.state.expanded_script_menu {
             return html! {};
@@ -355,27 +1361,293 @@ impl App {
         let update_script_input =
             link.callback(|e: InputEvent| Msg::UpdateScriptInput(get_text(e.target().unwrap())));
         html! {<>
+            <div class="box">
+                <div class="row">
+                    <button onclick={link.callback(|_| Msg::NewScript)}>{self.t("New Script")}</button>
+                    <button onclick={link.callback(|_| Msg::CloneScript)}>{self.t("Clone Current Script")}</button>
+                </div>
+            </div>
+            {self.view_script_lints()}
+            {self.view_script_feasibility(link)}
             {self.view_user_script_menu(link)}
             <div class="box">
                 <div class="row">
                     <div>
                         <p>
-                            {"Use the "}
-                            <a href="https://script.bloodontheclocktower.com/" target="_blank">{"BotC Script Builder"}</a>
-                            {" and export to clipboard (JSON):"}
+                            {self.t("Use the ")}
+                            <a href="https://script.bloodontheclocktower.com/" target="_blank">{self.t("BotC Script Builder")}</a>
+                            {self.t(" and export to clipboard (JSON):")}
                         </p>
                         <input type="text"
-                            placeholder="Paste your script here..."
+                            placeholder={self.t("Paste your script here...")}
                             oninput={update_script_input}
                             value={self.state.script_input.clone()}
                         />
-                        <button onclick={link.callback(|_| Msg::ImportScript)}>{"Import"}</button>
+                        <button onclick={link.callback(|_| Msg::ImportScript)}>{self.t("Import")}</button>
                     </div>
                 </div>
             </div>
+            {self.view_script_file_import(link)}
+            {self.view_pending_imports(link)}
+            {self.view_script_export(link)}
+            {self.view_distribution_tables(link)}
         </>}
     }
 
+    fn view_distribution_tables(&self, link: &Scope<Self>) -> Html {
+        let update_name = link.callback(|e: InputEvent| {
+            Msg::UpdateDistributionNameInput(get_text(e.target().unwrap()))
+        });
+        let update_text = link
+            .callback(|e: InputEvent| Msg::UpdateDistributionInput(get_text(e.target().unwrap())));
+        let user_tables = self.state.user_data.distributions.iter().map(|t| {
+            let name = t.name.clone();
+            html! {
+                <div class="row">
+                    <span title={t.to_text()}>{&t.name}</span>
+                    <button onclick={link.callback(move |_| Msg::DeleteDistribution(name.clone()))}>{"✖"}</button>
+                </div>
+            }
+        });
+        html! {
+            <div class="box">
+                <h4>{self.t("Distribution Tables")}</h4>
+                { for user_tables }
+                <input type="text" placeholder={self.t("Table name...")}
+                    oninput={update_name}
+                    value={self.state.distribution_name_input.clone()}
+                />
+                <textarea rows="5"
+                    placeholder={"script: 6/2/2/2\n5: 0/1/1\n6: 1/1/1"}
+                    oninput={update_text}
+                    value={self.state.distribution_input.clone()}
+                />
+                <button onclick={link.callback(|_| Msg::SaveDistribution)}>{self.t("Save Table")}</button>
+            </div>
+        }
+    }
+
+    /// The token pages are only built when they are downloaded, as that is slow for many tokens.
+    fn view_token_download(&self, link: &Scope<Self>) -> Html {
+        if self.state.selected.is_empty() {
+            return html! {};
+        }
+        html! {
+            <button onclick={link.callback(|_| Msg::DownloadTokens)}>{self.t("Download Tokens")}</button>
+        }
+    }
+
+    fn view_script_export(&self, link: &Scope<Self>) -> Html {
+        let Some(script) = self.state.get_current_script_data() else {
+            return html! {};
+        };
+        let distribution = script
+            .distribution
+            .is_some()
+            .then(|| self.state.distribution_table());
+        let json = export_script(
+            script,
+            &self.state.characters().collect::<Vec<_>>(),
+            distribution.as_ref(),
+        );
+        let href = format!(
+            "data:application/json;charset=utf-8,{}",
+            js_sys::encode_uri_component(&json)
+        );
+        let show_qr = link.callback(move |_| Msg::ShowQr(json.clone()));
+        html! {
+            <div class="box">
+                <div class="row">
+                    <a href={href} download={format!("{}.json", script.name)}>
+                        <button>{self.t("Export Current Script")}</button>
+                    </a>
+                    <button onclick={show_qr}>{self.t("QR")}</button>
+                </div>
+            </div>
+        }
+    }
+
+    fn view_script_file_import(&self, link: &Scope<Self>) -> Html {
+        let import_selected_files = link.callback(|e: Event| {
+            let input = e.target_unchecked_into::<HtmlInputElement>();
+            let files = input.files().map(get_files).unwrap_or_default();
+            // Allow selecting the same files again
+            input.set_value("");
+            Msg::ImportScriptFiles(files)
+        });
+        let import_dropped_files = link.callback(|e: DragEvent| {
+            e.prevent_default();
+            let files = e
+                .data_transfer()
+                .and_then(|dt| dt.files())
+                .map(get_files)
+                .unwrap_or_default();
+            Msg::ImportScriptFiles(files)
+        });
+        // Dropping is only allowed if dragover is cancelled
+        let allow_drop = Callback::from(|e: DragEvent| e.prevent_default());
+
+        html! {
+            <div class="box drop-zone" ondragover={allow_drop.clone()} ondragenter={allow_drop} ondrop={import_dropped_files}>
+                <div class="row">
+                    <div>
+                        <p>{self.t("Or select or drop script files (.json):")}</p>
+                        <input type="file" accept=".json,application/json" multiple=true
+                            onchange={import_selected_files}
+                        />
+                    </div>
+                </div>
+            </div>
+        }
+    }
+
+    fn view_pending_imports(&self, link: &Scope<Self>) -> Html {
+        let imports = self
+            .state
+            .pending_imports
+            .iter()
+            .enumerate()
+            .map(|(i, import)| self.view_pending_import(link, i, import));
+        html! { { for imports } }
+    }
+
+    fn view_pending_import(
+        &self,
+        link: &Scope<Self>,
+        index: usize,
+        import: &PendingImport,
+    ) -> Html {
+        let discard = html! {
+            <button onclick={link.callback(move |_| Msg::DiscardImport(index))}>{self.t("Discard")}</button>
+        };
+        let (report, buttons) = match &import.result {
+            Ok(import) => {
+                let report = &import.report;
+                let count = import.script.characters.len() - report.unknown.len();
+                let mut lines = vec![self.tf(
+                    "✅ \"{name}\" with {count} characters",
+                    &[("name", &import.script.name), ("count", &count)],
+                )];
+                if !report.unknown.is_empty() {
+                    lines.push(self.tf(
+                        "⚠️ Unknown characters (skipped): {ids}",
+                        &[("ids", &report.unknown.join(", "))],
+                    ));
+                }
+                if !report.duplicates.is_empty() {
+                    lines.push(self.tf(
+                        "⚠️ Duplicate characters (kept once): {ids}",
+                        &[("ids", &report.duplicates.join(", "))],
+                    ));
+                }
+                if !report.normalized.is_empty() {
+                    let normalized = report
+                        .normalized
+                        .iter()
+                        .map(|(from, to)| format!("{from} → {to}"))
+                        .collect::<Vec<_>>();
+                    lines.push(self.tf(
+                        "ℹ️ Normalized ids: {ids}",
+                        &[("ids", &normalized.join(", "))],
+                    ));
+                }
+                let buttons = html! { <>
+                    <button onclick={link.callback(move |_| Msg::ConfirmImport(index))}>{self.t("Import")}</button>
+                    {discard}
+                </> };
+                (lines, buttons)
+            }
+            Err(e) => (
+                vec![self.tf(
+                    "❌ Invalid script format ({error})",
+                    &[("error", &e.describe(self.language()))],
+                )],
+                discard,
+            ),
+        };
+        html! {
+            <div class="box">
+                <h4>{&import.source}</h4>
+                { for report.into_iter().map(|line| html! { <p>{line}</p> }) }
+                <div class="row">{buttons}</div>
+            </div>
+        }
+    }
+
+    fn view_script_lints(&self) -> Html {
+        let Some((_, lints)) = &self.state.script_lints else {
+            return html! {};
+        };
+        html! {
+            <div class="box">
+                <h4>{self.t("Script Check")}</h4>
+                if lints.is_empty() {
+                    <p>{self.t("✅ No problems found")}</p>
+                }
+                { for lints.iter().map(|lint| html! {
+                    <p>{"⚠️ "}{lint.describe(self.language(), |id| self.character_name(id))}</p>
+                }) }
+            </div>
+        }
+    }
+
+    fn view_script_feasibility(&self, link: &Scope<Self>) -> Html {
+        let set_travellers = link.callback(|e: Event| {
+            let count = get_text(e.target().unwrap()).parse::<u8>().unwrap_or(0);
+            Msg::SetAnalysisTravellers(count)
+        });
+        let Some((_, _, rows)) = &self.state.script_feasibility else {
+            return html! {};
+        };
+        let rows = rows.iter().map(|row| {
+            let players = match row.travellers {
+                0 => row.player_count.to_string(),
+                t => format!("{}+{t}", row.player_count),
+            };
+            let Some(distributions) = &row.distributions else {
+                return html! {
+                    <tr>
+                        <td>{players}</td>
+                        <td colspan="2">{self.t("Too many lists to count")}</td>
+                    </tr>
+                };
+            };
+            let total = row.total_lists().unwrap_or_default();
+            html! {
+                <tr>
+                    <td>{players}</td>
+                    <td>
+                        if distributions.is_empty() {
+                            {"❌"}
+                        }
+                        { for distributions.iter().map(|(d, n)| html! { <div>{format!("{d}: {n}")}</div> }) }
+                    </td>
+                    <td>{total}</td>
+                </tr>
+            }
+        });
+        html! {
+            <div class="box">
+                <h4>{self.t("Possible Lists")}</h4>
+                <div class="row">
+                    <label>{self.t("Travellers: ")}</label>
+                    <input type="number" min="0" max={self.state.max_analysis_travellers().to_string()}
+                        value={self.state.analysis_travellers.to_string()}
+                        onchange={set_travellers}
+                    />
+                </div>
+                <table class="feasibility">
+                    <tr>
+                        <th>{self.t("Players")}</th>
+                        <th>{self.t("T/O/M/D: Lists")}</th>
+                        <th>{self.t("Total")}</th>
+                    </tr>
+                    { for rows }
+                </table>
+            </div>
+        }
+    }
+
     fn view_user_script_menu(&self, link: &Scope<Self>) -> Html {
         // check if the current script is a custom user script
         if !self
@@ -390,26 +1662,53 @@ impl App {
 
         let update_script_rename_input = link
             .callback(|e: InputEvent| Msg::UpdateScriptRenameInput(get_text(e.target().unwrap())));
+        let set_distribution =
+            link.callback(|e: Event| Msg::SetScriptDistribution(get_text(e.target().unwrap())));
+        let current_table = self.state.distribution_table().name;
+        let tables = self.state.distribution_tables().map(|t| {
+            html! {
+                <option selected={t.name == current_table} value={t.name.clone()}>
+                    {&t.name}
+                </option>
+            }
+        });
         html! {
             <div class="box">
+                <div class="row">
+                    <label>{self.t("Distribution: ")}</label>
+                    <select onchange={set_distribution}>{ for tables }</select>
+                </div>
                 <div class="row">
                     <div>
                         <input type="text"
-                            placeholder="Rename current script..."
+                            placeholder={self.t("Rename current script...")}
                             oninput={update_script_rename_input}
                             value={self.state.script_rename_input.clone()}
                         />
-                        <button onclick={link.callback(|_| Msg::RenameScript)}>{"Rename"}</button>
+                        <button onclick={link.callback(|_| Msg::RenameScript)}>{self.t("Rename")}</button>
                     </div>
                 </div>
                 <div class="row">
-                    <button onclick={link.callback(|_| Msg::DeleteScript)}>{"Delete Current Script"}</button>
+                    <button onclick={link.callback(|_| Msg::EditScript)}>{self.t("Edit Current Script")}</button>
+                    <button onclick={link.callback(|_| Msg::DeleteScript)}>{self.t("Delete Current Script")}</button>
                 </div>
             </div>
         }
     }
 }
 
+/// Lets the browser download the content as a file.
+fn download(name: &str, mime: &str, content: &[u8]) {
+    let document = web_sys::window().unwrap().document().unwrap();
+    let Ok(a) = document.create_element("a") else {
+        return;
+    };
+    let a = a.unchecked_into::<HtmlAnchorElement>();
+    a.set_href(&format!("data:{mime};base64,{}", STANDARD.encode(content)));
+    a.set_download(name);
+    a.click();
+}
+
 fn get_text(target: EventTarget) -> String {
     target
         .value_of()
@@ -417,7 +1716,22 @@ fn get_text(target: EventTarget) -> String {
         .value()
 }
 
-fn clamped<T>(ctx: &Context<App>, min: T, max: T, msg: fn(T) -> Msg) -> Callback<Event>
+fn get_files(files: web_sys::FileList) -> Vec<gloo_file::File> {
+    gloo_file::FileList::from(files).to_vec()
+}
+
+/// Parses an optional type count, clamped to the largest count.
+fn parse_type_count(text: &str) -> Option<u8> {
+    let count = text.trim().parse::<u32>().ok()?;
+    Some(count.min(crate::consts::MAX_TYPE_COUNT.into()) as u8)
+}
+
+fn clamped<T>(
+    ctx: &Context<App>,
+    min: T,
+    max: T,
+    msg: impl Fn(T) -> Msg + 'static,
+) -> Callback<Event>
 where
     T: Copy + Ord + std::str::FromStr + 'static,
 {
@@ -432,3 +1746,20 @@ where
         }
     })
 }
+
+/// Shows the bundled copy of an icon and falls back to the remote URL if it fails to load.
+fn view_icon(url: Option<&str>, size: Option<&'static str>) -> Html {
+    let Some(url) = url else {
+        return html! { <img/> };
+    };
+    let remote = url.to_string();
+    let fallback = Callback::from(move |e: Event| {
+        let img = e.target_unchecked_into::<HtmlImageElement>();
+        if img.src() != remote {
+            img.set_src(&remote);
+        }
+    });
+    html! {
+        <img src={crate::icons::resolve(url).to_string()} width={size} height={size} onerror={fallback}/>
+    }
+}
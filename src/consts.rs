@@ -9,9 +9,24 @@ pub const FABLED_ICON: &str =
     "https://wiki.bloodontheclocktower.com/images/c/c9/Generic_fabled.png";
 pub const TRAVELLER_ICON: &str =
     "https://wiki.bloodontheclocktower.com/images/0/07/Generic_traveller.png";
+/// also linked in index.html, which is used until the bundled copy is set
+pub const FAVICON: &str = "https://wiki.bloodontheclocktower.com/images/6/6d/Icon_baron.png";
 
 pub const DEFAULT_SCRIPT: &str = "Trouble Brewing";
+pub const DEFAULT_DISTRIBUTION: &str = "Standard";
+
+pub const MIN_PLAYER_COUNT: u8 = 5;
+pub const MAX_PLAYER_COUNT: u8 = 15;
+pub const MAX_TRAVELLERS: u8 = 5;
+/// The most characters of a type a list or tag constraint can ask for.
+pub const MAX_TYPE_COUNT: u8 = 15;
 
 pub const MAX_GENERATION_ITERATIONS: usize = 10_000;
 
+pub const DEFAULT_BALANCE_TARGET: std::ops::RangeInclusive<i32> = -2..=2;
+
+pub const STATISTICS_RUNS: usize = 1_000;
+/// runs between redraws, so that the page stays responsive while the statistics are running
+pub const STATISTICS_CHUNK_RUNS: usize = 50;
+
 pub const STORAGE_KEY: &str = "botc.state";
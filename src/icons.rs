@@ -0,0 +1,86 @@
+use std::{collections::HashMap, sync::LazyLock};
+
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use wasm_bindgen::JsCast as _;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::Response;
+
+/// Remote icon URLs mapped to the copies bundled by `scripts/download_icons.py`.
+static MANIFEST: LazyLock<HashMap<String, String>> = LazyLock::new(|| {
+    serde_json::from_str(include_str!("../assets/icons/manifest.json")).unwrap_or_default()
+});
+
+/// The bundled copy of an icon, or the remote URL if it isn't in the manifest.
+pub fn resolve(url: &str) -> &str {
+    MANIFEST.get(url).map_or(url, String::as_str)
+}
+
+/// Points the favicon to the bundled copy, if there is one.
+pub fn set_favicon() {
+    let favicon = web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.get_element_by_id("favicon"));
+    if let Some(favicon) = favicon
+        && let Err(e) = favicon.set_attribute("href", resolve(crate::consts::FAVICON))
+    {
+        tracing::error!(?e);
+    }
+}
+
+/// The icon as a data URI, for documents that are used outside of the app
+/// such as downloaded token sheets. Loads the bundled copy, or the remote URL if there is none,
+/// which only works if its server allows it.
+pub async fn data_uri(url: &str) -> Option<String> {
+    let bundled = resolve(url);
+    if let Some(uri) = fetch_data_uri(bundled).await {
+        return Some(uri);
+    }
+    if bundled != url {
+        return fetch_data_uri(url).await;
+    }
+    None
+}
+
+async fn fetch_data_uri(url: &str) -> Option<String> {
+    let window = web_sys::window()?;
+    let response: Response = JsFuture::from(window.fetch_with_str(url))
+        .await
+        .ok()?
+        .dyn_into()
+        .ok()?;
+    if !response.ok() {
+        return None;
+    }
+    let mime = response
+        .headers()
+        .get("content-type")
+        .ok()
+        .flatten()
+        .unwrap_or_else(|| "image/png".to_string());
+    let buffer = JsFuture::from(response.array_buffer().ok()?).await.ok()?;
+    let bytes = js_sys::Uint8Array::new(&buffer).to_vec();
+    Some(format!("data:{mime};base64,{}", STANDARD.encode(bytes)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_bundled_icon_exists() {
+        let assets = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("assets");
+        let missing: Vec<&String> = MANIFEST
+            .values()
+            .filter(|path| !assets.join(path).exists())
+            .collect();
+        assert_eq!(missing, Vec::<&String>::new());
+    }
+
+    #[test]
+    fn resolves_to_the_bundled_copy_or_the_url() {
+        for (url, path) in MANIFEST.iter() {
+            assert_eq!(resolve(url), path);
+        }
+        assert_eq!(resolve("homebrew.png"), "homebrew.png");
+    }
+}
@@ -0,0 +1,766 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    ops::RangeInclusive,
+};
+
+use rand::{Rng, prelude::IndexedRandom as _};
+
+use super::{
+    character::{Character, Type},
+    condition::{self, Condition, TypeCond},
+    distribution::DistributionTable,
+    state::{exact_type_counts, list_sizes, validate_list},
+};
+
+/// The character types that are dealt to players.
+pub const PLAYER_TYPES: [Type; 4] = [Type::Townsfolk, Type::Outsider, Type::Minion, Type::Demon];
+
+/// Above this many subsets of special characters,
+/// enumerating the setups of a script takes too long to do while generating.
+pub const MAX_SPECIAL_SUBSETS: u128 = 1 << 10;
+
+/// Amount of characters of each player type in a list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Distribution {
+    pub townsfolk: u8,
+    pub outsiders: u8,
+    pub minions: u8,
+    pub demons: u8,
+}
+
+impl Distribution {
+    pub fn of(characters: &[&Character]) -> Self {
+        let mut distribution = Self::default();
+        for c in characters {
+            if let Some(count) = distribution.get_mut(c.r#type) {
+                *count += 1;
+            }
+        }
+        distribution
+    }
+
+    pub fn get(&self, r#type: Type) -> u8 {
+        match r#type {
+            Type::Townsfolk => self.townsfolk,
+            Type::Outsider => self.outsiders,
+            Type::Minion => self.minions,
+            Type::Demon => self.demons,
+            Type::Fabled | Type::Traveller => 0,
+        }
+    }
+
+    fn get_mut(&mut self, r#type: Type) -> Option<&mut u8> {
+        match r#type {
+            Type::Townsfolk => Some(&mut self.townsfolk),
+            Type::Outsider => Some(&mut self.outsiders),
+            Type::Minion => Some(&mut self.minions),
+            Type::Demon => Some(&mut self.demons),
+            Type::Fabled | Type::Traveller => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Distribution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}/{}/{}/{}",
+            self.townsfolk, self.outsiders, self.minions, self.demons
+        )
+    }
+}
+
+/// A class of valid lists that contain the same characters with conditions
+/// and only differ in which of the characters without conditions are picked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Setup {
+    /// indices into `Pool::special` of the characters with conditions in the lists
+    pub special: Vec<usize>,
+    /// amount of characters without conditions per type (including locked ones)
+    pub plain_counts: Distribution,
+    pub distribution: Distribution,
+    /// amount of characters in each list
+    pub size: usize,
+    /// amount of distinct lists in this class
+    pub lists: u128,
+}
+
+/// Characters of a script split by whether they can influence the validity of a list.
+/// Characters without conditions of the same type are interchangeable for validation,
+/// so only subsets of the special characters have to be enumerated.
+pub struct Pool<'a> {
+    pub special: Vec<&'a Character>,
+    pub plain: HashMap<Type, Vec<&'a Character>>,
+}
+
+impl<'a> Pool<'a> {
+    pub fn new(characters: &[&'a Character]) -> Self {
+        let characters: Vec<&Character> = characters
+            .iter()
+            .copied()
+            .filter(|c| PLAYER_TYPES.contains(&c.r#type))
+            .collect();
+        let referenced: Vec<&str> = characters
+            .iter()
+            .flat_map(|c| c.conditions.iter().flatten())
+            .flat_map(|cond| cond.characters())
+            .collect();
+
+        let mut special = Vec::new();
+        let mut plain: HashMap<Type, Vec<&Character>> = HashMap::new();
+        for c in characters {
+            if c.conditions.is_some() || referenced.iter().any(|id| c.has_id(id)) {
+                special.push(c);
+            } else {
+                plain.entry(c.r#type).or_default().push(c);
+            }
+        }
+        Self { special, plain }
+    }
+
+    fn plain(&self, r#type: Type) -> &[&'a Character] {
+        self.plain
+            .get(&r#type)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    fn all(&self) -> impl Iterator<Item = &'a Character> {
+        self.special
+            .iter()
+            .chain(self.plain.values().flatten())
+            .copied()
+    }
+}
+
+/// Enumerates every class of valid lists for the given player count.
+/// `locked` characters have to be part of every list.
+/// Returns `None` if there are more than `MAX_SPECIAL_SUBSETS` subsets of special characters,
+/// in which case lists have to be found with `sample_rejection` instead.
+///
+/// Assumes that the size of a list doesn't depend on the amount of Townsfolk.
+pub fn setups(
+    pool: &Pool,
+    locked: &[&Character],
+    player_count: u8,
+    type_counts: &HashMap<Type, BTreeSet<i8>>,
+) -> Option<Vec<Setup>> {
+    let available = Distribution::of(&pool.all().collect::<Vec<_>>());
+    let bounds = count_bounds(&pool.special, available, player_count, type_counts);
+    let subsets = PLAYER_TYPES
+        .into_iter()
+        .map(|t| {
+            let n = pool.special.iter().filter(|c| c.r#type == t).count();
+            (0..=n.min(*bounds[&t].end()))
+                .map(|k| binomial(n, k))
+                .fold(0u128, u128::saturating_add)
+        })
+        .fold(1u128, u128::saturating_mul);
+    if subsets > MAX_SPECIAL_SUBSETS {
+        tracing::info!("Too many subsets of special characters to enumerate: {subsets}");
+        return None;
+    }
+
+    let is_locked = |c: &Character| locked.iter().any(|l| l.id() == c.id());
+    let locked_plain = |r#type: Type| pool.plain(r#type).iter().filter(|&&c| is_locked(c)).count();
+    let [townsfolk, outsiders, minions, demons] = PLAYER_TYPES.map(|t| {
+        let mut plain = pool.plain(t).to_vec();
+        // Locked characters first so that they are always picked
+        plain.sort_by_key(|&c| !is_locked(c));
+        (plain, locked_plain(t))
+    });
+
+    let mut setups = Vec::new();
+    for_each_subset(
+        pool,
+        &bounds,
+        &is_locked,
+        &mut Vec::new(),
+        &mut |subset: &[usize]| {
+            let special: Vec<&Character> = subset.iter().map(|&i| pool.special[i]).collect();
+            // Amount of plain characters of a type that can be in a valid list with `special`
+            let bounds = count_bounds(&special, available, player_count, type_counts);
+            let plain_range = |r#type: Type, (plain, locked): &(Vec<&Character>, usize)| {
+                let special = special.iter().filter(|c| c.r#type == r#type).count();
+                let range = &bounds[&r#type];
+                let min = range.start().saturating_sub(special).max(*locked);
+                match range.end().checked_sub(special) {
+                    Some(max) => min..=max.min(plain.len()),
+                    #[allow(clippy::reversed_empty_ranges)]
+                    None => 1..=0,
+                }
+            };
+
+            for o in plain_range(Type::Outsider, &outsiders) {
+                for m in plain_range(Type::Minion, &minions) {
+                    for d in plain_range(Type::Demon, &demons) {
+                        let others = special.len() + o + m + d;
+                        let build = |t: usize| {
+                            let mut list = special.clone();
+                            list.extend(&townsfolk.0[..t]);
+                            list.extend(&outsiders.0[..o]);
+                            list.extend(&minions.0[..m]);
+                            list.extend(&demons.0[..d]);
+                            list
+                        };
+
+                        let first_guess = (player_count as usize)
+                            .saturating_sub(others)
+                            .min(townsfolk.0.len());
+                        for size in list_sizes(&build(first_guess), player_count) {
+                            let t = size.checked_sub(others);
+                            let Some(t) = t.filter(|&t| townsfolk.1 <= t && t <= townsfolk.0.len())
+                            else {
+                                continue;
+                            };
+
+                            let list = build(t);
+                            if !validate_list(&list, player_count, type_counts.clone()) {
+                                continue;
+                            }
+
+                            let lists = [
+                                (&townsfolk, t),
+                                (&outsiders, o),
+                                (&minions, m),
+                                (&demons, d),
+                            ]
+                            .into_iter()
+                            .map(|((plain, locked), n)| binomial(plain.len() - locked, n - locked))
+                            .fold(1u128, u128::saturating_mul);
+                            setups.push(Setup {
+                                special: subset.to_vec(),
+                                plain_counts: Distribution {
+                                    townsfolk: t as u8,
+                                    outsiders: o as u8,
+                                    minions: m as u8,
+                                    demons: d as u8,
+                                },
+                                distribution: Distribution::of(&list),
+                                size,
+                                lists,
+                            });
+                        }
+                    }
+                }
+            }
+        },
+    );
+    Some(setups)
+}
+
+/// Calls `f` with every subset of the special characters that contains the locked ones
+/// and has no more characters of a type than `bounds` allow.
+fn for_each_subset(
+    pool: &Pool,
+    bounds: &HashMap<Type, RangeInclusive<usize>>,
+    is_locked: &impl Fn(&Character) -> bool,
+    subset: &mut Vec<usize>,
+    f: &mut impl FnMut(&[usize]),
+) {
+    // Subsets are built in index order, so characters before `start` can't be added anymore
+    let start = subset.last().map_or(0, |&last| last + 1);
+    if !pool.special[start..].iter().any(|&c| is_locked(c)) {
+        f(subset);
+    }
+    for i in start..pool.special.len() {
+        let r#type = pool.special[i].r#type;
+        let count = subset
+            .iter()
+            .filter(|&&j| pool.special[j].r#type == r#type)
+            .count();
+        if count < *bounds[&r#type].end() {
+            subset.push(i);
+            for_each_subset(pool, bounds, is_locked, subset, f);
+            subset.pop();
+        }
+        // Skipping a locked character would leave it out of every following subset
+        if is_locked(pool.special[i]) {
+            break;
+        }
+    }
+}
+
+/// Lowest and highest amount of characters of each type that a valid list
+/// containing `characters` can have, given how many characters of each type are `available`.
+/// This allows more than `validate_list` does, so lists outside of it can be skipped.
+fn count_bounds(
+    characters: &[&Character],
+    available: Distribution,
+    player_count: u8,
+    type_counts: &HashMap<Type, BTreeSet<i8>>,
+) -> HashMap<Type, RangeInclusive<usize>> {
+    let mut down: HashMap<Type, i32> = HashMap::new();
+    let mut up: HashMap<Type, i32> = HashMap::new();
+    let mut any = BTreeSet::new();
+    let mut none = BTreeSet::new();
+    let mut chosen = BTreeSet::new();
+    let mut variables: HashMap<Type, (i32, i32)> = HashMap::new();
+    let mut replaced = Vec::new();
+    for c in characters {
+        // Each character only applies one of its alternatives
+        let mut c_down: HashMap<Type, i32> = HashMap::new();
+        let mut c_up: HashMap<Type, i32> = HashMap::new();
+        for alternative in condition::alternatives(c.conditions.iter().flatten(), player_count) {
+            let mut a_down: HashMap<Type, i32> = HashMap::new();
+            let mut a_up: HashMap<Type, i32> = HashMap::new();
+            for condition in alternative {
+                match condition {
+                    Condition::Type {
+                        r#type,
+                        amount: TypeCond::Add(amounts),
+                    } => {
+                        let min = amounts.first().map_or(0, |&a| a as i32);
+                        let max = amounts.last().map_or(0, |&a| a as i32);
+                        *a_down.entry(r#type).or_default() += (-min).max(0);
+                        *a_up.entry(r#type).or_default() += max.max(0);
+                    }
+                    Condition::Type {
+                        r#type,
+                        amount: TypeCond::SaturatingSub(amounts),
+                    } => {
+                        *a_down.entry(r#type).or_default() +=
+                            amounts.last().map_or(0, |&a| a as i32);
+                    }
+                    Condition::Type {
+                        r#type,
+                        amount: TypeCond::Any,
+                    } => {
+                        any.insert(r#type);
+                    }
+                    Condition::Type {
+                        r#type,
+                        amount: TypeCond::None,
+                    } => {
+                        none.insert(r#type);
+                    }
+                    Condition::ChosenAfterSetup { chosen_after_setup } => {
+                        chosen.insert(chosen_after_setup);
+                    }
+                    Condition::Replace {
+                        replace,
+                        with,
+                        amount,
+                    } => replaced.push((replace, with, amount as usize)),
+                    Condition::Variable {
+                        r#type, min, max, ..
+                    } => {
+                        let (min, max) = (min as i32, max as i32);
+                        let range = variables.entry(r#type).or_insert((min, max));
+                        *range = (range.0.min(min), range.1.max(max));
+                    }
+                    _ => {}
+                }
+            }
+            for (shift, a_shift) in [(&mut c_down, a_down), (&mut c_up, a_up)] {
+                for (r#type, amount) in a_shift {
+                    let max = shift.entry(r#type).or_default();
+                    *max = (*max).max(amount);
+                }
+            }
+        }
+        for (shift, c_shift) in [(&mut down, c_down), (&mut up, c_up)] {
+            for (r#type, amount) in c_shift {
+                *shift.entry(r#type).or_default() += amount;
+            }
+        }
+    }
+
+    let mut bounds: HashMap<Type, RangeInclusive<usize>> = PLAYER_TYPES
+        .into_iter()
+        .map(|r#type| {
+            let all = available.get(r#type) as i32;
+            // Types without allowed counts are only checked if a condition modifies them
+            let (min, max) = match type_counts.get(&r#type) {
+                Some(counts) if !any.contains(&r#type) && !counts.is_empty() => {
+                    let mut min = *counts.first().unwrap() as i32 - down.get(&r#type).unwrap_or(&0);
+                    let mut max = *counts.last().unwrap() as i32 + up.get(&r#type).unwrap_or(&0);
+                    if none.contains(&r#type) || chosen.contains(&r#type) {
+                        min = 0;
+                    }
+                    if let Some(&(v_min, v_max)) = variables.get(&r#type) {
+                        min = min.min(v_min);
+                        max = max.max(v_max);
+                    }
+                    (min.max(0), max.clamp(0, all))
+                }
+                _ => (0, all),
+            };
+            (r#type, min as usize..=max as usize)
+        })
+        .collect();
+    // Replaced characters can't be more than their own type allows
+    for (replace, with, amount) in replaced {
+        let added = bounds[&replace].end() * amount;
+        let all = available.get(with) as usize;
+        if let Some(range) = bounds.get_mut(&with) {
+            *range = *range.start()..=(range.end() + added).min(all);
+        }
+    }
+    bounds
+}
+
+/// Picks one of the lists described by `setups` uniformly at random.
+/// Returns `None` if there are no valid lists.
+pub fn sample<'a, R: Rng + ?Sized>(
+    pool: &Pool<'a>,
+    setups: &[Setup],
+    locked: &[&Character],
+    rng: &mut R,
+) -> Option<Vec<&'a Character>> {
+    let total = setups
+        .iter()
+        .fold(0u128, |acc, s| acc.saturating_add(s.lists));
+    if total == 0 {
+        return None;
+    }
+
+    // Pick a setup weighted by the amount of lists it contains
+    let mut index = rng.random_range(0..total);
+    let setup = setups.iter().find(|s| {
+        if index < s.lists {
+            true
+        } else {
+            index -= s.lists;
+            false
+        }
+    })?;
+
+    // Then pick the characters without conditions uniformly
+    let is_locked = |c: &Character| locked.iter().any(|l| l.id() == c.id());
+    let mut list: Vec<&Character> = setup.special.iter().map(|&i| pool.special[i]).collect();
+    for r#type in PLAYER_TYPES {
+        let (locked, unlocked): (Vec<&Character>, Vec<&Character>) =
+            pool.plain(r#type).iter().partition(|&&c| is_locked(c));
+        let missing = setup.plain_counts.get(r#type) as usize - locked.len();
+        list.extend(locked);
+        list.extend(unlocked.choose_multiple(rng, missing));
+    }
+    Some(list)
+}
+
+/// Picks a random list and returns it if it is valid,
+/// for scripts with too many setups to enumerate.
+/// Unlike `sample` this is not uniform: lists whose conditions are easy to satisfy
+/// are found more often.
+pub fn sample_rejection<'a, R: Rng + ?Sized>(
+    pool: &Pool<'a>,
+    locked: &[&Character],
+    player_count: u8,
+    type_counts: &HashMap<Type, BTreeSet<i8>>,
+    rng: &mut R,
+) -> Option<Vec<&'a Character>> {
+    let (locked, unlocked): (Vec<&Character>, Vec<&Character>) = pool
+        .all()
+        .partition(|c| locked.iter().any(|l| l.id() == c.id()));
+    let mut size = player_count as usize;
+    // Conditions can change the size of a list,
+    // so an invalid list is retried once with a size its conditions allow
+    for _ in 0..2 {
+        let mut list = locked.clone();
+        list.extend(unlocked.choose_multiple(rng, size.checked_sub(locked.len())?));
+        if validate_list(&list, player_count, type_counts.clone()) {
+            return Some(list);
+        }
+        let sizes: Vec<usize> = list_sizes(&list, player_count).into_iter().collect();
+        size = *sizes.choose(rng)?;
+    }
+    None
+}
+
+/// The valid distributions for one player count and how many distinct lists have each.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Feasibility {
+    pub player_count: u8,
+    pub travellers: u8,
+    /// `None` if there are too many setups to count them
+    pub distributions: Option<BTreeMap<Distribution, u128>>,
+}
+
+impl Feasibility {
+    pub fn total_lists(&self) -> Option<u128> {
+        let distributions = self.distributions.as_ref()?;
+        Some(
+            distributions
+                .values()
+                .fold(0, |acc, &n| acc.saturating_add(n)),
+        )
+    }
+}
+
+/// Counts the valid lists of a script for every player count of the distribution table.
+/// Travellers don't change the distribution, they only multiply the amount of lists.
+pub fn feasibility(
+    characters: &[&Character],
+    table: &DistributionTable,
+    travellers: u8,
+) -> Vec<Feasibility> {
+    let pool = Pool::new(characters);
+    let script_travellers = characters
+        .iter()
+        .filter(|c| c.r#type == Type::Traveller)
+        .count();
+    let traveller_choices = binomial(script_travellers, travellers as usize);
+
+    table
+        .player_counts()
+        .filter_map(|player_count| {
+            let (outsiders, minions, demons) = table.type_counts(player_count)?;
+            let type_counts = exact_type_counts(outsiders, minions, demons);
+            let distributions = setups(&pool, &[], player_count, &type_counts).map(|setups| {
+                let mut distributions = BTreeMap::new();
+                for setup in setups {
+                    let lists = setup.lists.saturating_mul(traveller_choices);
+                    let n: &mut u128 = distributions.entry(setup.distribution).or_default();
+                    *n = n.saturating_add(lists);
+                }
+                distributions.retain(|_, &mut n| n > 0);
+                distributions
+            });
+            Some(Feasibility {
+                player_count,
+                travellers,
+                distributions,
+            })
+        })
+        .collect()
+}
+
+pub fn binomial(n: usize, k: usize) -> u128 {
+    if k > n {
+        return 0;
+    }
+    let k = k.min(n - k);
+    (0..k).fold(1u128, |acc, i| {
+        acc.saturating_mul((n - i) as u128) / (i as u128 + 1)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use rand::{SeedableRng as _, rngs::StdRng};
+
+    use super::*;
+    use crate::logic::{data::IncludedData, state::exact_type_counts};
+
+    fn small_script() -> Vec<Character> {
+        let outsiders = |name, r#type, amounts: &str| {
+            Character::test(name, r#type).with_conditions(&format!(
+                r#"[{{"type": "OUTSIDER", "amount": {{"ADD": {amounts}}}}}]"#
+            ))
+        };
+        vec![
+            Character::test("Chef", Type::Townsfolk),
+            Character::test("Empath", Type::Townsfolk),
+            Character::test("Monk", Type::Townsfolk),
+            outsiders("Balloonist", Type::Townsfolk, "[0, 1]"),
+            Character::test("Butler", Type::Outsider),
+            Character::test("Saint", Type::Outsider),
+            Character::test("Spy", Type::Minion),
+            outsiders("Baron", Type::Minion, "[2]"),
+            outsiders("Godfather", Type::Minion, "[-1, 1]"),
+            Character::test("Imp", Type::Demon),
+            outsiders("Fang Gu", Type::Demon, "[1]"),
+        ]
+    }
+
+    fn key(list: &[&Character]) -> BTreeSet<String> {
+        list.iter().map(|c| c.id()).collect()
+    }
+
+    /// All valid lists found by checking every subset of the script.
+    fn brute_force(
+        characters: &[&Character],
+        locked: &[&Character],
+        player_count: u8,
+        type_counts: &HashMap<Type, BTreeSet<i8>>,
+    ) -> BTreeSet<BTreeSet<String>> {
+        let mut lists = BTreeSet::new();
+        for mask in 0u32..(1 << characters.len()) {
+            let list: Vec<&Character> = (0..characters.len())
+                .filter(|&i| mask & (1 << i) != 0)
+                .map(|i| characters[i])
+                .collect();
+            if !locked.iter().all(|l| list.iter().any(|c| c.id() == l.id())) {
+                continue;
+            }
+            if validate_list(&list, player_count, type_counts.clone()) {
+                lists.insert(key(&list));
+            }
+        }
+        lists
+    }
+
+    /// Checks that the setups of the script count as many lists as checking every subset finds.
+    fn assert_counts_match_brute_force(
+        script: &[Character],
+        player_count: u8,
+        type_counts: HashMap<Type, BTreeSet<i8>>,
+    ) {
+        let characters: Vec<&Character> = script.iter().collect();
+        let pool = Pool::new(&characters);
+        let expected = brute_force(&characters, &[], player_count, &type_counts);
+        let counted: u128 = setups(&pool, &[], player_count, &type_counts)
+            .unwrap()
+            .iter()
+            .map(|s| s.lists)
+            .sum();
+        assert_eq!(counted, expected.len() as u128, "{player_count} players");
+    }
+
+    #[test]
+    fn setups_count_every_valid_list() {
+        let script = small_script();
+        for (player_count, outsiders) in [(5, 0), (6, 1), (7, 0)] {
+            assert_counts_match_brute_force(
+                &script,
+                player_count,
+                exact_type_counts(outsiders, 1, 1),
+            );
+        }
+    }
+
+    #[test]
+    fn setups_count_lists_with_combinators() {
+        let monk = Character::test("Monk", Type::Townsfolk).with_conditions(
+            r#"[
+                {"any_of": [{"character": "chef"}, {"extra_characters": 1}]},
+                {"not": {"all_of": [{"character": "butler"}, {"character": "saint"}]}},
+                {"excludes": "imp"},
+                {"min": 7, "then": [{"type": "MINION", "amount": {"ADD": [-1]}}]}
+            ]"#,
+        );
+        let conditions = monk.conditions.as_deref().unwrap();
+        assert!(matches!(conditions[2], Condition::Excludes { .. }));
+        assert!(matches!(conditions[3], Condition::WhenPlayers { .. }));
+
+        let mut script = small_script();
+        script.push(monk);
+        script.remove(2);
+        for (player_count, outsiders) in [(5, 0), (6, 1), (7, 2)] {
+            assert_counts_match_brute_force(
+                &script,
+                player_count,
+                exact_type_counts(outsiders, 1, 1),
+            );
+        }
+    }
+
+    #[test]
+    fn setups_count_lists_with_type_modifiers() {
+        let mut script = small_script();
+        script.extend([
+            Character::test("Atheist", Type::Townsfolk).with_conditions(
+                r#"[{"type": "DEMON", "amount": "NONE"}, {"type": "MINION", "amount": "NONE"}]"#,
+            ),
+            Character::test("Vigormortis", Type::Demon)
+                .with_conditions(r#"[{"type": "OUTSIDER", "amount": {"SATURATING_SUB": [1]}}]"#),
+            Character::test("Lil' Monsta", Type::Demon)
+                .with_conditions(r#"[{"replace": "DEMON", "with": "MINION", "amount": 1}]"#),
+            Character::test("Kazali", Type::Demon).with_conditions(
+                r#"[{"chosen_after_setup": "MINION"}, {"type": "OUTSIDER", "amount": "ANY"}]"#,
+            ),
+            Character::test("Xaan", Type::Minion)
+                .with_conditions(r#"[{"variable": "X", "type": "OUTSIDER", "min": 1, "max": 2}]"#),
+            Character::test("Summoner", Type::Minion)
+                .with_conditions(r#"[{"chosen_after_setup": "DEMON"}]"#),
+        ]);
+        for (player_count, outsiders) in [(5, 0), (6, 1), (7, 0)] {
+            assert_counts_match_brute_force(
+                &script,
+                player_count,
+                exact_type_counts(outsiders, 1, 1),
+            );
+        }
+    }
+
+    #[test]
+    fn large_scripts_fall_back_to_rejection_sampling() {
+        let data: IncludedData = serde_json::from_str(include_str!("../data.json")).unwrap();
+        let mut script = data.characters;
+        // More characters with conditions than fit into a 64 bit mask
+        script.extend((0..70).map(|i| {
+            Character::test(&format!("Homebrew {i}"), Type::Townsfolk)
+                .with_conditions(r#"[{"extra_characters": 0}]"#)
+        }));
+        let characters: Vec<&Character> = script.iter().collect();
+        let pool = Pool::new(&characters);
+        let type_counts = exact_type_counts(0, 2, 1);
+
+        let start = std::time::Instant::now();
+        assert!(setups(&pool, &[], 10, &type_counts).is_none());
+        assert!(start.elapsed().as_secs() < 1, "took {:?}", start.elapsed());
+
+        let mut rng = StdRng::seed_from_u64(10);
+        let list = (0..crate::consts::MAX_GENERATION_ITERATIONS)
+            .find_map(|_| sample_rejection(&pool, &[], 10, &type_counts, &mut rng))
+            .expect("no valid list found");
+        assert!(validate_list(&list, 10, type_counts));
+    }
+
+    #[test]
+    fn samples_are_uniform() {
+        let script = small_script();
+        let characters: Vec<&Character> = script.iter().collect();
+        let pool = Pool::new(&characters);
+        let type_counts = exact_type_counts(0, 1, 1);
+        let expected = brute_force(&characters, &[], 5, &type_counts);
+        let setups = setups(&pool, &[], 5, &type_counts).unwrap();
+
+        let mut rng = StdRng::seed_from_u64(5);
+        assert_chi_squared(&expected, || {
+            key(&sample(&pool, &setups, &[], &mut rng).unwrap())
+        });
+    }
+
+    #[test]
+    fn samples_with_locked_characters_are_uniform() {
+        let script = small_script();
+        let characters: Vec<&Character> = script.iter().collect();
+        let pool = Pool::new(&characters);
+        let locked = [characters[0], characters[8]];
+        let type_counts = exact_type_counts(1, 1, 1);
+        let expected = brute_force(&characters, &locked, 6, &type_counts);
+        let setups = setups(&pool, &locked, 6, &type_counts).unwrap();
+
+        let mut rng = StdRng::seed_from_u64(6);
+        assert_chi_squared(&expected, || {
+            key(&sample(&pool, &setups, &locked, &mut rng).unwrap())
+        });
+    }
+
+    /// Pearson's chi-squared test against the uniform distribution over `expected`.
+    fn assert_chi_squared(
+        expected: &BTreeSet<BTreeSet<String>>,
+        mut sample: impl FnMut() -> BTreeSet<String>,
+    ) {
+        const SAMPLES_PER_LIST: usize = 200;
+        assert!(expected.len() > 1, "not enough lists to test");
+        let samples = expected.len() * SAMPLES_PER_LIST;
+        let mut counts: BTreeMap<BTreeSet<String>, usize> = BTreeMap::new();
+        for _ in 0..samples {
+            let list = sample();
+            assert!(expected.contains(&list), "invalid list sampled: {list:?}");
+            *counts.entry(list).or_default() += 1;
+        }
+
+        let e = SAMPLES_PER_LIST as f64;
+        let chi_squared: f64 = expected
+            .iter()
+            .map(|list| {
+                let o = *counts.get(list).unwrap_or(&0) as f64;
+                (o - e).powi(2) / e
+            })
+            .sum();
+        // Mean + 5 standard deviations of the chi-squared distribution
+        let df = (expected.len() - 1) as f64;
+        let limit = df + 5.0 * (2.0 * df).sqrt();
+        assert!(
+            chi_squared < limit,
+            "chi squared {chi_squared} exceeds {limit} for {} lists",
+            expected.len()
+        );
+    }
+}
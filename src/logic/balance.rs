@@ -0,0 +1,79 @@
+use std::collections::BTreeMap;
+
+use super::character::{Category, Character};
+
+/// A rough estimate of how much a list helps the good team compared to how much it disrupts it.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Balance {
+    pub categories: BTreeMap<Category, usize>,
+    /// information and protection on good characters
+    pub good: i32,
+    /// misinformation and droisoning on any character and extra deaths on evil characters
+    pub evil: i32,
+}
+
+impl Balance {
+    pub fn of(characters: &[&Character]) -> Self {
+        let mut balance = Self::default();
+        for c in characters {
+            for &category in &c.categories {
+                *balance.categories.entry(category).or_default() += 1;
+                match category {
+                    Category::Information | Category::Protection if c.r#type.is_good() => {
+                        balance.good += 1
+                    }
+                    Category::Misinformation | Category::Droisoning => balance.evil += 1,
+                    Category::ExtraDeath if !c.r#type.is_good() => balance.evil += 1,
+                    _ => {}
+                }
+            }
+        }
+        balance
+    }
+
+    /// Positive scores favour the good team, negative scores the evil team.
+    pub fn score(&self) -> i32 {
+        self.good - self.evil
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logic::character::Type;
+
+    #[test]
+    fn scores_good_and_evil_categories() {
+        let character = |name, r#type, categories: &[Category]| Character {
+            categories: categories.to_vec(),
+            ..Character::test(name, r#type)
+        };
+        let characters = [
+            character("Fortune Teller", Type::Townsfolk, &[Category::Information]),
+            character(
+                "Innkeeper",
+                Type::Townsfolk,
+                &[Category::Protection, Category::Droisoning],
+            ),
+            // information and extra deaths only count on the team that benefits from them
+            character("Moonchild", Type::Outsider, &[Category::ExtraDeath]),
+            character(
+                "Widow",
+                Type::Minion,
+                &[Category::Information, Category::Droisoning],
+            ),
+            character(
+                "Vortox",
+                Type::Demon,
+                &[Category::Misinformation, Category::ExtraDeath],
+            ),
+        ];
+        let balance = Balance::of(&characters.iter().collect::<Vec<_>>());
+        assert_eq!((balance.good, balance.evil), (2, 4));
+        assert_eq!(balance.score(), -2);
+        assert_eq!(balance.categories[&Category::Information], 2);
+        assert_eq!(balance.categories[&Category::Droisoning], 2);
+        assert_eq!(balance.categories[&Category::ExtraDeath], 2);
+        assert_eq!(Balance::of(&[]).score(), 0);
+    }
+}
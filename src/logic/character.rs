@@ -1,32 +1,126 @@
 use serde::{Deserialize, Serialize};
 
-use super::condition::Condition;
+use super::{condition::Condition, reveal::Disguise, seating::SeatingCondition};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Character {
+    /// official id, e.g. "fortune_teller", derived from the name if it is missing
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub id: String,
     pub name: String,
     pub description: String,
     pub r#type: Type,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub categories: Vec<Category>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
     pub icon: Option<String>,
     pub conditions: Option<Vec<Condition>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub seating: Vec<SeatingCondition>,
+    /// shown to the storyteller when the character is in play
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub setup_note: Option<String>,
+    /// types the script should have characters of that aren't in play
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub spares: Vec<Type>,
+    /// texts of the character's reminder tokens
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reminders: Vec<String>,
+    /// what the player is shown if they don't know their character
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disguise: Option<Disguise>,
 }
 
 impl Character {
     pub fn id(&self) -> String {
-        self.name
-            .chars()
-            .filter(|c| c.is_ascii_alphanumeric())
-            .map(|c| c.to_ascii_lowercase())
-            .collect()
+        if self.id.is_empty() {
+            self.name
+                .split(|c: char| !c.is_ascii_alphanumeric() && c != '\'')
+                .map(|word| word.replace('\'', "").to_ascii_lowercase())
+                .filter(|word| !word.is_empty())
+                .collect::<Vec<_>>()
+                .join("_")
+        } else {
+            self.id.clone()
+        }
     }
 
+    /// The id scripts use, without numbers, e.g. "village_idiot" for all Village Idiots.
     pub fn id_no_numeric(&self) -> String {
+        self.id()
+            .trim_end_matches(|c: char| c.is_ascii_digit() || c == '_')
+            .to_string()
+    }
+
+    /// The id this character had before ids were explicit: its name without separators.
+    pub fn legacy_id(&self) -> String {
         self.name
             .chars()
-            .filter(|c| c.is_ascii_alphabetic())
+            .filter(|c| c.is_ascii_alphanumeric())
             .map(|c| c.to_ascii_lowercase())
             .collect()
     }
+
+    pub fn has_id(&self, id: &str) -> bool {
+        self.id() == id
+    }
+
+    /// Like `has_id`, but for the ids without numbers that scripts use.
+    pub fn has_script_id(&self, id: &str) -> bool {
+        self.id_no_numeric() == id
+    }
+
+    /// Whether `id` is the legacy id of this character. Separators are ignored,
+    /// so "fortune-teller" matches as well. Only used to look up ids that no character has,
+    /// as legacy ids of homebrew characters can collide with official ids.
+    pub fn has_legacy_id(&self, id: &str) -> bool {
+        self.legacy_id() == id.replace(['-', '_'], "")
+    }
+
+    /// Like `has_legacy_id`, but for the ids without numbers that scripts use.
+    pub fn has_legacy_script_id(&self, id: &str) -> bool {
+        self.legacy_id()
+            .trim_end_matches(|c: char| c.is_ascii_digit())
+            == id.replace(['-', '_'], "")
+    }
+}
+
+#[cfg(test)]
+impl Character {
+    /// A character with nothing but a name and a type.
+    pub fn test(name: &str, r#type: Type) -> Self {
+        Self {
+            id: String::new(),
+            name: name.to_string(),
+            description: String::new(),
+            r#type,
+            categories: Vec::new(),
+            tags: Vec::new(),
+            icon: None,
+            conditions: None,
+            seating: Vec::new(),
+            setup_note: None,
+            spares: Vec::new(),
+            reminders: Vec::new(),
+            disguise: None,
+        }
+    }
+
+    /// The character with the conditions parsed from `json`.
+    pub fn with_conditions(self, json: &str) -> Self {
+        Self {
+            conditions: Some(serde_json::from_str(json).unwrap()),
+            ..self
+        }
+    }
+
+    pub fn with_disguise(self, disguise: Disguise) -> Self {
+        Self {
+            disguise: Some(disguise),
+            ..self
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -41,7 +135,7 @@ pub enum Type {
 }
 
 impl Type {
-    pub fn plural_str(&self) -> &str {
+    pub fn plural_str(&self) -> &'static str {
         match self {
             Type::Townsfolk => "Townsfolk",
             Type::Outsider => "Outsiders",
@@ -52,6 +146,10 @@ impl Type {
         }
     }
 
+    pub fn is_good(&self) -> bool {
+        matches!(self, Type::Townsfolk | Type::Outsider)
+    }
+
     pub fn icon(&self) -> &str {
         match self {
             Type::Townsfolk => crate::consts::TOWNSFOLK_ICON,
@@ -63,3 +161,74 @@ impl Type {
         }
     }
 }
+
+/// What a character does to the game, used to estimate how balanced a list is.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Category {
+    Information,
+    Protection,
+    Misinformation,
+    Droisoning,
+    ExtraDeath,
+    WinConditionChanger,
+}
+
+impl Category {
+    pub const ALL: [Category; 6] = [
+        Category::Information,
+        Category::Protection,
+        Category::Misinformation,
+        Category::Droisoning,
+        Category::ExtraDeath,
+        Category::WinConditionChanger,
+    ];
+
+    pub fn str(&self) -> &'static str {
+        match self {
+            Category::Information => "Information",
+            Category::Protection => "Protection",
+            Category::Misinformation => "Misinformation",
+            Category::Droisoning => "Droisoning",
+            Category::ExtraDeath => "Extra Death",
+            Category::WinConditionChanger => "Win Condition Changer",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn has_id() {
+        let fortune_teller = Character::test("Fortune Teller", Type::Townsfolk);
+        assert_eq!(fortune_teller.id(), "fortune_teller");
+        assert!(fortune_teller.has_id("fortune_teller"));
+        assert!(!fortune_teller.has_id("fortuneteller"));
+        for id in ["fortuneteller", "fortune-teller", "fortune_teller"] {
+            assert!(fortune_teller.has_legacy_id(id), "{id}");
+        }
+        assert!(!fortune_teller.has_legacy_id("fortune"));
+
+        let lil_monsta = Character {
+            id: "lilmonsta_homebrew".to_string(),
+            ..Character::test("Lil' Monsta", Type::Demon)
+        };
+        assert!(lil_monsta.has_id("lilmonsta_homebrew"));
+        assert!(!lil_monsta.has_id("lil_monsta"));
+    }
+
+    #[test]
+    fn has_script_id() {
+        let village_idiot = Character::test("Village Idiot 2", Type::Townsfolk);
+        assert_eq!(village_idiot.id_no_numeric(), "village_idiot");
+        assert!(village_idiot.has_script_id("village_idiot"));
+        assert!(!village_idiot.has_script_id("villageidiot"));
+        for id in ["villageidiot", "village-idiot"] {
+            assert!(village_idiot.has_legacy_script_id(id), "{id}");
+        }
+        assert!(!village_idiot.has_script_id("village_idiot_2"));
+        assert!(!village_idiot.has_legacy_script_id("village"));
+    }
+}
@@ -13,6 +13,197 @@ pub enum Condition {
     Type { r#type: Type, amount: TypeCond },
     /// add additional characters past the player count
     ExtraCharacters { extra_characters: ExtraCharacters },
+    /// at least one of the conditions has to hold
+    AnyOf { any_of: Vec<Condition> },
+    /// all of the conditions have to hold
+    AllOf { all_of: Vec<Condition> },
+    /// the condition must not hold
+    Not { not: Box<Condition> },
+    /// forbid a character
+    Excludes {
+        #[serde(rename = "excludes")]
+        character: String,
+    },
+    /// the conditions only apply if the player count is within min and max
+    WhenPlayers {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        min: Option<u8>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max: Option<u8>,
+        then: Vec<Condition>,
+    },
+    /// the characters of a type are in play but not dealt,
+    /// instead `amount` characters of another type are dealt for each of them
+    Replace {
+        replace: Type,
+        with: Type,
+        amount: u8,
+    },
+    /// the characters of a type are chosen after setup instead of being dealt
+    ChosenAfterSetup { chosen_after_setup: Type },
+    /// the character is dealt to more than one player
+    Seats { seats: Seats },
+    /// the amount of characters of a type is a named value between min and max,
+    /// which overrides every other modification of the type count
+    Variable {
+        variable: String,
+        r#type: Type,
+        min: u8,
+        max: u8,
+    },
+}
+
+impl Condition {
+    /// ids of the characters this condition refers to
+    pub fn characters(&self) -> Vec<&str> {
+        match self {
+            Condition::Character { character } | Condition::Excludes { character } => {
+                vec![character]
+            }
+            Condition::AnyOf { any_of: conditions }
+            | Condition::AllOf { all_of: conditions }
+            | Condition::WhenPlayers {
+                then: conditions, ..
+            } => conditions.iter().flat_map(Condition::characters).collect(),
+            Condition::Not { not } => not.characters(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Mutable ids of the characters this condition refers to, for migrating them.
+    pub fn characters_mut(&mut self) -> Vec<&mut String> {
+        match self {
+            Condition::Character { character } | Condition::Excludes { character } => {
+                vec![character]
+            }
+            Condition::AnyOf { any_of: conditions }
+            | Condition::AllOf { all_of: conditions }
+            | Condition::WhenPlayers {
+                then: conditions, ..
+            } => conditions
+                .iter_mut()
+                .flat_map(Condition::characters_mut)
+                .collect(),
+            Condition::Not { not } => not.characters_mut(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Conditions inside of a `Not` that can't be negated, which are ignored.
+    pub fn unsupported_negations(&self) -> Vec<&Condition> {
+        match self {
+            Condition::AnyOf { any_of: conditions }
+            | Condition::AllOf { all_of: conditions }
+            | Condition::WhenPlayers {
+                then: conditions, ..
+            } => conditions
+                .iter()
+                .flat_map(Condition::unsupported_negations)
+                .collect(),
+            Condition::Not { not } => not.unnegatable(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// The parts of this condition that `negated_alternatives` ignores.
+    fn unnegatable(&self) -> Vec<&Condition> {
+        match self {
+            Condition::Character { .. } | Condition::Excludes { .. } => Vec::new(),
+            Condition::AnyOf { any_of: conditions }
+            | Condition::AllOf { all_of: conditions }
+            | Condition::WhenPlayers {
+                then: conditions, ..
+            } => conditions.iter().flat_map(Condition::unnegatable).collect(),
+            Condition::Not { not } => not.unsupported_negations(),
+            condition => vec![condition],
+        }
+    }
+
+    /// Every way this condition can be satisfied with the player count,
+    /// each as a list of conditions without `AnyOf`, `AllOf`, `Not` and `WhenPlayers`.
+    pub fn alternatives(&self, player_count: u8) -> Vec<Vec<Condition>> {
+        match self {
+            Condition::AnyOf { any_of } => any_of
+                .iter()
+                .flat_map(|c| c.alternatives(player_count))
+                .collect(),
+            Condition::AllOf { all_of } => alternatives(all_of, player_count),
+            Condition::Not { not } => not.negated_alternatives(player_count),
+            Condition::WhenPlayers { min, max, then } => {
+                if applies(*min, *max, player_count) {
+                    alternatives(then, player_count)
+                } else {
+                    vec![Vec::new()]
+                }
+            }
+            condition => vec![vec![condition.clone()]],
+        }
+    }
+
+    /// Every way this condition can fail with the player count.
+    /// Only requirements on characters can be negated,
+    /// everything else is ignored and reported by `unsupported_negations`.
+    fn negated_alternatives(&self, player_count: u8) -> Vec<Vec<Condition>> {
+        match self {
+            Condition::Character { character } => vec![vec![Condition::Excludes {
+                character: character.clone(),
+            }]],
+            Condition::Excludes { character } => vec![vec![Condition::Character {
+                character: character.clone(),
+            }]],
+            Condition::AnyOf { any_of } => any_of
+                .iter()
+                .map(|c| c.negated_alternatives(player_count))
+                .fold(vec![Vec::new()], product),
+            Condition::AllOf { all_of } => all_of
+                .iter()
+                .flat_map(|c| c.negated_alternatives(player_count))
+                .collect(),
+            Condition::Not { not } => not.alternatives(player_count),
+            Condition::WhenPlayers { min, max, then } => {
+                if applies(*min, *max, player_count) {
+                    then.iter()
+                        .flat_map(|c| c.negated_alternatives(player_count))
+                        .collect()
+                } else {
+                    // the condition always holds, so it can't fail
+                    Vec::new()
+                }
+            }
+            condition => {
+                tracing::warn!("Ignoring negated condition {condition:?}");
+                vec![Vec::new()]
+            }
+        }
+    }
+}
+
+/// Every way all of the conditions can be satisfied at once with the player count.
+pub fn alternatives<'a>(
+    conditions: impl IntoIterator<Item = &'a Condition>,
+    player_count: u8,
+) -> Vec<Vec<Condition>> {
+    conditions
+        .into_iter()
+        .map(|c| c.alternatives(player_count))
+        .fold(vec![Vec::new()], product)
+}
+
+fn applies(min: Option<u8>, max: Option<u8>, player_count: u8) -> bool {
+    min.is_none_or(|min| min <= player_count) && max.is_none_or(|max| player_count <= max)
+}
+
+/// Combines every alternative of `a` with every alternative of `b`.
+fn product(a: Vec<Vec<Condition>>, b: Vec<Vec<Condition>>) -> Vec<Vec<Condition>> {
+    a.iter()
+        .flat_map(|a| {
+            b.iter().map(move |b| {
+                let mut combined = a.clone();
+                combined.extend(b.iter().cloned());
+                combined
+            })
+        })
+        .collect()
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
@@ -24,6 +215,30 @@ pub enum TypeCond {
     Add(BTreeSet<i8>),
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(untagged)]
+pub enum Seats {
+    /// dealt to exactly this many players
+    Const(u8),
+    /// dealt to more than half of the players
+    Majority(Majority),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Majority {
+    Majority,
+}
+
+impl Seats {
+    pub fn count(&self, player_count: u8) -> u8 {
+        match self {
+            Seats::Const(seats) => *seats,
+            Seats::Majority(_) => player_count / 2 + 1,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 #[serde(untagged)]
 pub enum ExtraCharacters {
@@ -32,3 +247,55 @@ pub enum ExtraCharacters {
     /// add (count of $Type characters - u8) extra characters
     Type(Type, u8),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alternatives_at(json: &str, player_count: u8) -> Vec<Vec<Condition>> {
+        let condition: Condition = serde_json::from_str(json).unwrap();
+        condition.alternatives(player_count)
+    }
+
+    fn character(id: &str) -> Condition {
+        Condition::Character {
+            character: id.to_string(),
+        }
+    }
+
+    #[test]
+    fn when_players_applies_within_inclusive_bounds() {
+        let json = r#"{"min": 7, "max": 9, "then": [{"character": "baron"}]}"#;
+        let applied = vec![vec![character("baron")]];
+        let ignored = vec![Vec::<Condition>::new()];
+        assert_eq!(alternatives_at(json, 6), ignored);
+        assert_eq!(alternatives_at(json, 7), applied);
+        assert_eq!(alternatives_at(json, 9), applied);
+        assert_eq!(alternatives_at(json, 10), ignored);
+    }
+
+    #[test]
+    fn when_players_with_one_bound_is_open_on_the_other_side() {
+        let min = r#"{"min": 7, "then": [{"character": "baron"}]}"#;
+        assert_eq!(alternatives_at(min, 6), [Vec::<Condition>::new()]);
+        assert_eq!(alternatives_at(min, 7), [[character("baron")]]);
+        assert_eq!(alternatives_at(min, 15), [[character("baron")]]);
+
+        let max = r#"{"max": 6, "then": [{"character": "baron"}]}"#;
+        assert_eq!(alternatives_at(max, 5), [[character("baron")]]);
+        assert_eq!(alternatives_at(max, 6), [[character("baron")]]);
+        assert_eq!(alternatives_at(max, 7), [Vec::<Condition>::new()]);
+    }
+
+    #[test]
+    fn negated_when_players_only_fails_within_bounds() {
+        let json = r#"{"not": {"min": 7, "max": 7, "then": [{"character": "baron"}]}}"#;
+        let excluded = vec![vec![Condition::Excludes {
+            character: "baron".to_string(),
+        }]];
+        // outside of the bounds the inner condition always holds, so its negation can't
+        assert_eq!(alternatives_at(json, 6), Vec::<Vec<Condition>>::new());
+        assert_eq!(alternatives_at(json, 7), excluded);
+        assert_eq!(alternatives_at(json, 8), Vec::<Vec<Condition>>::new());
+    }
+}
@@ -1,48 +1,309 @@
 use serde::{Deserialize, Serialize};
 
-use super::character::Character;
+use super::{
+    character::Character,
+    distribution::DistributionTable,
+    i18n::Language,
+    tags::{TagConstraint, UserTags},
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct IncludedData {
     pub characters: Vec<Character>,
     pub scripts: Vec<Script>,
+    #[serde(default)]
+    pub jinxes: Vec<Jinx>,
+    #[serde(default)]
+    pub distributions: Vec<DistributionTable>,
+}
+
+/// Two characters with a special rule when they are on a script together.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Jinx {
+    pub characters: [String; 2],
+    pub description: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
 pub struct UserData {
     pub characters: Vec<Character>,
     pub scripts: Vec<Script>,
+    #[serde(default)]
+    pub tags: UserTags,
+    #[serde(default)]
+    pub tag_constraints: Vec<TagConstraint>,
+    #[serde(default)]
+    pub distributions: Vec<DistributionTable>,
+    #[serde(default)]
+    pub language: Language,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Default)]
 pub struct Script {
     pub name: String,
     pub characters: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// URL of the script logo
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logo: Option<String>,
+    /// homebrew rules for the storyteller
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bootlegger: Vec<String>,
+    /// custom night order overrides
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_night: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub other_night: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub hide_title: bool,
+    /// name of the distribution table, the standard one if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub distribution: Option<String>,
+}
+
+/// A parsed script together with everything that was changed or is suspicious about it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptImport {
+    pub script: Script,
+    /// distribution table the script was exported with
+    pub distribution: Option<DistributionTable>,
+    pub report: ImportReport,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ImportReport {
+    /// ids that don't match any known character
+    pub unknown: Vec<String>,
+    /// (original, canonical) ids that were mapped to the id of a known character
+    pub normalized: Vec<(String, String)>,
+    /// ids that appeared more than once, only the first occurrence is kept
+    pub duplicates: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportError {
+    /// the script is not valid JSON
+    Json {
+        message: String,
+        line: usize,
+        column: usize,
+    },
+    /// the script file could not be read
+    Read(String),
+}
+
+impl From<serde_json::Error> for ImportError {
+    fn from(e: serde_json::Error) -> Self {
+        let (line, column) = (e.line(), e.column());
+        let location = format!(" at line {line} column {column}");
+        let message = e.to_string();
+        let message = message.strip_suffix(&location).unwrap_or(&message);
+        Self::Json {
+            message: message.to_string(),
+            line,
+            column,
+        }
+    }
+}
+
+impl ImportError {
+    /// Describes the error, the messages of the JSON parser and the browser stay in English.
+    pub fn describe(&self, language: Language) -> String {
+        match self {
+            // serde_json uses line 0 for errors that don't have a location
+            Self::Json {
+                message, line: 0, ..
+            } => message.clone(),
+            Self::Json {
+                message,
+                line,
+                column,
+            } => language.tf(
+                "line {line}, column {column}: {message}",
+                &[("line", line), ("column", column), ("message", message)],
+            ),
+            Self::Read(message) => language.tf(
+                "failed to read the file: {message}",
+                &[("message", message)],
+            ),
+        }
+    }
 }
 
-pub fn import_script(json: &str) -> Result<Script, serde_json::Error> {
+/// Parses a script in the format of the official script tool.
+/// Unknown ids are not detected here since that requires the character data.
+pub fn import_script(json: &str) -> Result<ScriptImport, ImportError> {
     let vec: Vec<serde_json::Value> = serde_json::from_str(json)?;
 
     let objects = vec.iter().filter_map(|v| v.as_object()).collect::<Vec<_>>();
 
-    let name = objects
+    let meta = objects
         .iter()
-        .find(|v| v.get("id").is_some_and(|id| id.as_str() == Some("_meta")))
-        .and_then(|v| v.get("name"))
-        .and_then(|v| v.as_str())
-        .and_then(|s| (!s.is_empty()).then_some(s))
-        .unwrap_or("My Script")
-        .to_string();
-
-    let characters = objects
+        .find(|v| v.get("id").is_some_and(|id| id.as_str() == Some("_meta")));
+    let meta_str = |key: &str| {
+        meta.and_then(|v| v.get(key))
+            .and_then(|v| v.as_str())
+            .and_then(|s| (!s.is_empty()).then(|| s.to_string()))
+    };
+    let meta_strs = |key: &str| {
+        meta.and_then(|v| v.get(key))
+            .and_then(|v| v.as_array())
+            .map(|a| {
+                a.iter()
+                    .filter_map(|v| v.as_str())
+                    .map(|s| s.to_string())
+                    .collect::<Vec<_>>()
+            })
+    };
+
+    let name = meta_str("name").unwrap_or_else(|| "My Script".to_string());
+    let distribution: Option<DistributionTable> = meta
+        .and_then(|v| v.get("distribution"))
+        .and_then(|v| serde_json::from_value(v.clone()).ok());
+
+    let ids = objects
         .iter()
         .filter_map(|v| v.get("id"))
         .filter_map(|id| id.as_str())
         .filter(|id| !id.is_empty())
         .filter(|id| !id.starts_with('_'))
-        .chain(vec.iter().filter_map(|v| v.as_str()))
-        .map(|id| id.replace(['-', '_'], ""))
+        .chain(vec.iter().filter_map(|v| v.as_str()));
+
+    let mut report = ImportReport::default();
+    let mut characters: Vec<String> = Vec::new();
+    for id in ids {
+        let id = id.to_string();
+        if characters.contains(&id) {
+            if !report.duplicates.contains(&id) {
+                report.duplicates.push(id);
+            }
+        } else {
+            characters.push(id);
+        }
+    }
+
+    let script = Script {
+        name,
+        characters,
+        author: meta_str("author"),
+        logo: meta_str("logo"),
+        bootlegger: meta_strs("bootlegger").unwrap_or_default(),
+        first_night: meta_strs("firstNight"),
+        other_night: meta_strs("otherNight"),
+        hide_title: meta
+            .and_then(|v| v.get("hideTitle"))
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
+        distribution: distribution.as_ref().map(|t| t.name.clone()),
+    };
+
+    Ok(ScriptImport {
+        script,
+        distribution,
+        report,
+    })
+}
+
+/// Serializes a script in the format of the official script tool,
+/// referring to the characters by their official ids.
+/// The distribution table is included so that it can be imported together with the script.
+pub fn export_script(
+    script: &Script,
+    characters: &[&Character],
+    distribution: Option<&DistributionTable>,
+) -> String {
+    let official_id = |id: &String| {
+        characters
+            .iter()
+            .find(|c| c.has_script_id(id))
+            .or_else(|| characters.iter().find(|c| c.has_legacy_script_id(id)))
+            .map_or_else(|| id.clone(), |c| c.id_no_numeric())
+    };
+    let official_ids = |ids: &Vec<String>| ids.iter().map(official_id).collect::<Vec<_>>();
+
+    let mut meta = serde_json::Map::new();
+    meta.insert("id".into(), "_meta".into());
+    meta.insert("name".into(), script.name.clone().into());
+    if let Some(author) = &script.author {
+        meta.insert("author".into(), author.clone().into());
+    }
+    if let Some(logo) = &script.logo {
+        meta.insert("logo".into(), logo.clone().into());
+    }
+    if script.hide_title {
+        meta.insert("hideTitle".into(), true.into());
+    }
+    if !script.bootlegger.is_empty() {
+        meta.insert("bootlegger".into(), script.bootlegger.clone().into());
+    }
+    if let Some(first_night) = &script.first_night {
+        meta.insert("firstNight".into(), official_ids(first_night).into());
+    }
+    if let Some(other_night) = &script.other_night {
+        meta.insert("otherNight".into(), official_ids(other_night).into());
+    }
+    if let Some(distribution) = distribution {
+        meta.insert(
+            "distribution".into(),
+            serde_json::to_value(distribution).unwrap(),
+        );
+    }
+
+    let json: Vec<serde_json::Value> = std::iter::once(meta.into())
+        .chain(script.characters.iter().map(|id| official_id(id).into()))
         .collect();
+    serde_json::to_string(&json).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logic::character::Type;
+
+    #[test]
+    fn export_uses_official_ids() {
+        let characters = [
+            Character::test("Fortune Teller", Type::Townsfolk),
+            Character::test("Lil' Monsta", Type::Demon),
+        ];
+        let characters: Vec<&Character> = characters.iter().collect();
+        let import = import_script(
+            r#"[
+                {"id": "_meta", "name": "Test", "author": "Me", "firstNight": ["fortuneteller"]},
+                {"id": "fortune-teller"},
+                "lilmonsta",
+                "homebrew"
+            ]"#,
+        )
+        .unwrap();
+        let export = export_script(&import.script, &characters, None);
+
+        let reimport = import_script(&export).unwrap();
+        assert_eq!(
+            reimport.script.characters,
+            ["fortune_teller", "lil_monsta", "homebrew"]
+        );
+        assert_eq!(
+            reimport.script.first_night,
+            Some(vec!["fortune_teller".to_string()])
+        );
+        assert_eq!(reimport.script.name, "Test");
+        assert_eq!(reimport.script.author.as_deref(), Some("Me"));
+        assert_eq!(export_script(&reimport.script, &characters, None), export);
+    }
 
-    Ok(Script { name, characters })
+    #[test]
+    fn distribution_is_exported_with_the_script() {
+        let table = DistributionTable::parse("Tiny", "3: 0/0/1\n4: 0/1/1").unwrap();
+        let script = Script {
+            name: "Tiny Script".to_string(),
+            characters: vec!["imp".to_string()],
+            distribution: Some("Tiny".to_string()),
+            ..Default::default()
+        };
+        let import = import_script(&export_script(&script, &[], Some(&table))).unwrap();
+        assert_eq!(import.script, script);
+        assert_eq!(import.distribution, Some(table));
+    }
 }
@@ -0,0 +1,82 @@
+use std::{collections::BTreeMap, ops::RangeInclusive};
+
+use serde::{Deserialize, Serialize};
+
+/// The base type counts for each supported player count.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DistributionTable {
+    pub name: String,
+    /// usual amount of Townsfolk, Outsiders, Minions and Demons on a script
+    pub script_counts: [usize; 4],
+    /// Outsider, Minion and Demon counts by player count
+    pub rows: BTreeMap<u8, [u8; 3]>,
+}
+
+impl DistributionTable {
+    pub fn type_counts(&self, player_count: u8) -> Option<(u8, u8, u8)> {
+        self.rows
+            .get(&player_count)
+            .map(|&[outsiders, minions, demons]| (outsiders, minions, demons))
+    }
+
+    pub fn player_counts(&self) -> impl Iterator<Item = u8> {
+        self.rows.keys().copied()
+    }
+
+    /// The lowest and highest player count the table has a row for.
+    pub fn player_count_range(&self) -> Option<RangeInclusive<u8>> {
+        let (&min, _) = self.rows.first_key_value()?;
+        let (&max, _) = self.rows.last_key_value()?;
+        Some(min..=max)
+    }
+
+    /// Parses one `players: outsiders/minions/demons` line per player count and
+    /// an optional `script: townsfolk/outsiders/minions/demons` line.
+    pub fn parse(name: &str, text: &str) -> Result<Self, String> {
+        let mut table = Self {
+            name: name.trim().to_string(),
+            script_counts: [13, 4, 4, 4],
+            rows: BTreeMap::new(),
+        };
+        if table.name.is_empty() {
+            return Err("The table needs a name".to_string());
+        }
+
+        for line in text.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let invalid = || format!("Invalid line: \"{line}\"");
+            let (key, counts) = line.split_once(':').ok_or_else(invalid)?;
+            let counts = counts
+                .split('/')
+                .map(|c| c.trim().parse::<u8>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| invalid())?;
+            match (key.trim(), counts.as_slice()) {
+                ("script", &[t, o, m, d]) => {
+                    table.script_counts = [t, o, m, d].map(usize::from);
+                }
+                (players, &[o, m, d]) => {
+                    let players = players.parse::<u8>().map_err(|_| invalid())?;
+                    table.rows.insert(players, [o, m, d]);
+                }
+                _ => return Err(invalid()),
+            }
+        }
+
+        if table.rows.is_empty() {
+            return Err("The table needs at least one player count".to_string());
+        }
+        Ok(table)
+    }
+
+    pub fn to_text(&self) -> String {
+        let [t, o, m, d] = self.script_counts;
+        std::iter::once(format!("script: {t}/{o}/{m}/{d}"))
+            .chain(
+                self.rows
+                    .iter()
+                    .map(|(players, [o, m, d])| format!("{players}: {o}/{m}/{d}")),
+            )
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
@@ -0,0 +1,45 @@
+use super::{character::Character, data::Script, i18n::Language};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptEditor {
+    pub script: Script,
+    /// name of the user script that is being edited, `None` for a new script
+    pub editing: Option<String>,
+    pub search: String,
+}
+
+impl ScriptEditor {
+    pub fn new(script: Script, editing: Option<String>) -> Self {
+        Self {
+            script,
+            editing,
+            search: String::new(),
+        }
+    }
+
+    /// Scripts refer to characters by their id without numbers,
+    /// e.g. "village_idiot" includes all Village Idiots.
+    pub fn contains(&self, character: &Character) -> bool {
+        self.script
+            .characters
+            .iter()
+            .any(|id| character.has_script_id(id))
+    }
+
+    pub fn toggle(&mut self, id: String) {
+        if let Some(i) = self.script.characters.iter().position(|c| c == &id) {
+            self.script.characters.remove(i);
+        } else {
+            self.script.characters.push(id);
+        }
+    }
+
+    /// Matches the English name as well as the name in the selected language.
+    pub fn matches_search(&self, character: &Character, language: Language) -> bool {
+        let search = self.search.trim().to_lowercase();
+        search.is_empty()
+            || [character.name.as_str(), language.name(character)]
+                .iter()
+                .any(|name| name.to_lowercase().contains(&search))
+    }
+}
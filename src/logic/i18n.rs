@@ -0,0 +1,254 @@
+use std::{collections::HashMap, fmt::Display, sync::LazyLock};
+
+use serde::{Deserialize, Serialize};
+
+use super::character::Character;
+
+static GERMAN: LazyLock<Bundle> =
+    LazyLock::new(|| serde_json::from_str(include_str!("../i18n/de.json")).unwrap());
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    #[default]
+    English,
+    German,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::German];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::German => "de",
+        }
+    }
+
+    /// The name of the language in the language itself.
+    pub fn native_name(&self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::German => "Deutsch",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|l| l.code() == code)
+    }
+
+    /// English is the language of data.json and the UI, so it has no bundle.
+    fn bundle(&self) -> Option<&'static Bundle> {
+        match self {
+            Language::English => None,
+            Language::German => Some(&GERMAN),
+        }
+    }
+
+    /// Translates an English UI string, falling back to English if there is no translation.
+    pub fn t(&self, text: &'static str) -> &'static str {
+        self.bundle()
+            .and_then(|b| b.ui.get(text))
+            .map_or(text, String::as_str)
+    }
+
+    /// Translates an English UI string with `{name}` placeholders and fills them in.
+    /// Translations may reorder the placeholders.
+    pub fn tf(&self, text: &'static str, args: &[(&str, &dyn Display)]) -> String {
+        args.iter()
+            .fold(self.t(text).to_string(), |text, (name, value)| {
+                text.replace(&format!("{{{name}}}"), &value.to_string())
+            })
+    }
+
+    /// Whether the name and description of the character are translated.
+    pub fn translates(&self, character: &Character) -> bool {
+        self.bundle().is_none() || self.character_text(character).is_some()
+    }
+
+    pub fn name<'a>(&self, character: &'a Character) -> &'a str {
+        self.character_text(character)
+            .and_then(|t| t.name.as_deref())
+            .unwrap_or(&character.name)
+    }
+
+    pub fn description<'a>(&self, character: &'a Character) -> &'a str {
+        self.character_text(character)
+            .and_then(|t| t.description.as_deref())
+            .unwrap_or(&character.description)
+    }
+
+    pub fn setup_note<'a>(&self, character: &'a Character) -> Option<&'a str> {
+        self.character_text(character)
+            .and_then(|t| t.setup_note.as_deref())
+            .or(character.setup_note.as_deref())
+    }
+
+    /// The reminder tokens of the character, in English unless all of them are translated.
+    pub fn reminders<'a>(&self, character: &'a Character) -> Vec<&'a str> {
+        match self
+            .character_text(character)
+            .and_then(|t| t.reminders.as_ref())
+        {
+            Some(reminders) if reminders.len() == character.reminders.len() => {
+                reminders.iter().map(String::as_str).collect()
+            }
+            _ => character.reminders.iter().map(String::as_str).collect(),
+        }
+    }
+
+    fn character_text(&self, character: &Character) -> Option<&'static CharacterText> {
+        self.bundle()?.characters.get(&character.id())
+    }
+}
+
+/// Translations for one language, keyed by character id and by the English UI string.
+#[derive(Debug, Clone, Deserialize, Default)]
+struct Bundle {
+    #[serde(default)]
+    characters: HashMap<String, CharacterText>,
+    #[serde(default)]
+    ui: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct CharacterText {
+    name: Option<String>,
+    description: Option<String>,
+    setup_note: Option<String>,
+    reminders: Option<Vec<String>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logic::{
+        character::{Category, Type},
+        data::IncludedData,
+        seating::Alignment,
+    };
+
+    /// Every file that passes UI strings to `t` or `tf`.
+    const SOURCES: [&str; 6] = [
+        include_str!("../app.rs"),
+        include_str!("data.rs"),
+        include_str!("lint.rs"),
+        include_str!("notes.rs"),
+        include_str!("seating.rs"),
+        include_str!("state.rs"),
+    ];
+
+    /// The string literals passed to `t` and `tf` in the source.
+    fn ui_strings(source: &str) -> Vec<String> {
+        let mut strings = Vec::new();
+        for call in [".t(", ".tf("] {
+            for (i, _) in source.match_indices(call) {
+                let Some(literal) = source[i + call.len()..].trim_start().strip_prefix('"') else {
+                    continue;
+                };
+                let mut string = String::new();
+                let mut chars = literal.chars();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' => match chars.next() {
+                            Some('n') => string.push('\n'),
+                            Some(c) => string.push(c),
+                            None => break,
+                        },
+                        c => string.push(c),
+                    }
+                }
+                strings.push(string);
+            }
+        }
+        strings
+    }
+
+    fn placeholders(text: &str) -> Vec<&str> {
+        let mut placeholders: Vec<&str> = text
+            .split('{')
+            .skip(1)
+            .filter_map(|s| s.split_once('}').map(|(name, _)| name))
+            .collect();
+        placeholders.sort();
+        placeholders
+    }
+
+    #[test]
+    fn every_ui_string_is_translated() {
+        let types = [
+            Type::Townsfolk,
+            Type::Outsider,
+            Type::Minion,
+            Type::Demon,
+            Type::Fabled,
+            Type::Traveller,
+        ];
+        let mut strings: Vec<String> = SOURCES.iter().flat_map(|s| ui_strings(s)).collect();
+        assert!(strings.len() > 50, "the UI strings weren't found");
+        strings.extend(types.map(|t| t.plural_str().to_string()));
+        strings.extend(Category::ALL.map(|c| c.str().to_string()));
+        strings.extend([Alignment::Good, Alignment::Evil].map(|a| a.str().to_string()));
+
+        let mut missing: Vec<&String> = strings
+            .iter()
+            .filter(|text| !GERMAN.ui.contains_key(*text))
+            .collect();
+        missing.sort();
+        missing.dedup();
+        assert_eq!(missing, Vec::<&String>::new());
+    }
+
+    #[test]
+    fn every_character_is_translated() {
+        let data: IncludedData = serde_json::from_str(include_str!("../data.json")).unwrap();
+        for c in &data.characters {
+            let text = GERMAN.characters.get(&c.id());
+            let text = text.unwrap_or_else(|| panic!("{} is not translated", c.id()));
+            assert!(text.name.is_some(), "name of {}", c.id());
+            assert!(text.description.is_some(), "description of {}", c.id());
+            assert_eq!(
+                text.setup_note.is_some(),
+                c.setup_note.is_some(),
+                "setup note of {}",
+                c.id()
+            );
+            assert_eq!(
+                text.reminders.as_ref().map_or(0, Vec::len),
+                c.reminders.len(),
+                "reminders of {}",
+                c.id()
+            );
+        }
+        assert_eq!(GERMAN.characters.len(), data.characters.len());
+    }
+
+    #[test]
+    fn reminders_fall_back_to_english() {
+        let drunk = Character {
+            id: "drunk".to_string(),
+            reminders: vec!["Drunk".to_string()],
+            ..Character::test("Drunk", Type::Outsider)
+        };
+        assert_eq!(Language::German.reminders(&drunk), ["Betrunken"]);
+        assert_eq!(Language::English.reminders(&drunk), ["Drunk"]);
+
+        // reminders that don't match the translated ones aren't mixed with them
+        let homebrew_drunk = Character {
+            reminders: vec!["Drunk".to_string(), "Hungover".to_string()],
+            ..drunk
+        };
+        assert_eq!(
+            Language::German.reminders(&homebrew_drunk),
+            ["Drunk", "Hungover"]
+        );
+    }
+
+    #[test]
+    fn translations_keep_the_placeholders() {
+        for (text, translation) in &GERMAN.ui {
+            assert_eq!(placeholders(text), placeholders(translation), "{text}");
+        }
+    }
+}
@@ -0,0 +1,394 @@
+use super::{
+    analysis::{self, PLAYER_TYPES, Pool},
+    character::{Character, Type},
+    condition::{self, Condition},
+    data::Jinx,
+    distribution::DistributionTable,
+    i18n::Language,
+    state::exact_type_counts,
+};
+
+/// A problem with a script, referring to characters by their ids.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Lint {
+    FewCharacters {
+        r#type: Type,
+        count: usize,
+        usual: usize,
+    },
+    NoDemon,
+    NoValidList {
+        player_counts: Vec<u8>,
+    },
+    /// the script has too many setups to check for valid lists
+    TooManySetups {
+        player_counts: Vec<u8>,
+    },
+    MissingCharacter {
+        character: String,
+        required: String,
+    },
+    Jinx {
+        characters: [String; 2],
+        description: String,
+    },
+    /// a condition inside of a `not` that can't be negated and is ignored
+    UnsupportedNegation {
+        character: String,
+        condition: String,
+    },
+}
+
+impl Lint {
+    /// Describes the problem, `name` gives the name of a character by its id.
+    pub fn describe(&self, language: Language, name: impl Fn(&str) -> String) -> String {
+        match self {
+            Lint::FewCharacters {
+                r#type,
+                count,
+                usual,
+            } => language.tf(
+                "Only {count} {type} (usually {usual})",
+                &[
+                    ("count", count),
+                    ("type", &language.t(r#type.plural_str())),
+                    ("usual", usual),
+                ],
+            ),
+            Lint::NoDemon => language.t("No Demon").to_string(),
+            Lint::NoValidList { player_counts } => {
+                let player_counts: Vec<String> =
+                    player_counts.iter().map(|p| p.to_string()).collect();
+                language.tf(
+                    "No valid list for {player_counts} players",
+                    &[("player_counts", &player_counts.join(", "))],
+                )
+            }
+            Lint::TooManySetups { player_counts } => {
+                let player_counts: Vec<String> =
+                    player_counts.iter().map(|p| p.to_string()).collect();
+                language.tf(
+                    "Too many setups to verify that there are valid lists for {player_counts} players",
+                    &[("player_counts", &player_counts.join(", "))],
+                )
+            }
+            Lint::MissingCharacter {
+                character,
+                required,
+            } => language.tf(
+                "{character} requires \"{required}\", which is not on the script",
+                &[
+                    ("character", &name(character)),
+                    ("required", &name(required)),
+                ],
+            ),
+            Lint::Jinx {
+                characters: [a, b],
+                description,
+            } => language.tf(
+                "{a} and {b} are jinxed: {description}",
+                &[
+                    ("a", &name(a)),
+                    ("b", &name(b)),
+                    ("description", description),
+                ],
+            ),
+            Lint::UnsupportedNegation {
+                character,
+                condition,
+            } => language.tf(
+                "{character} has a condition that can't be negated and is ignored: not {condition}",
+                &[("character", &name(character)), ("condition", condition)],
+            ),
+        }
+    }
+}
+
+pub fn lint_script(
+    characters: &[&Character],
+    table: &DistributionTable,
+    jinxes: &[Jinx],
+) -> Vec<Lint> {
+    let mut lints = Vec::new();
+
+    for (r#type, usual) in PLAYER_TYPES.into_iter().zip(table.script_counts) {
+        let count = characters.iter().filter(|c| c.r#type == r#type).count();
+        if count < usual {
+            lints.push(Lint::FewCharacters {
+                r#type,
+                count,
+                usual,
+            });
+        }
+    }
+
+    if !characters.iter().any(|c| c.r#type == Type::Demon) {
+        lints.push(Lint::NoDemon);
+    }
+
+    let pool = Pool::new(characters);
+    let mut invalid = Vec::new();
+    let mut unverified = Vec::new();
+    for player_count in table.player_counts() {
+        let Some((outsiders, minions, demons)) = table.type_counts(player_count) else {
+            continue;
+        };
+        let type_counts = exact_type_counts(outsiders, minions, demons);
+        match analysis::setups(&pool, &[], player_count, &type_counts) {
+            Some(setups) if setups.is_empty() => invalid.push(player_count),
+            Some(_) => {}
+            None => unverified.push(player_count),
+        }
+    }
+    if !invalid.is_empty() {
+        lints.push(Lint::NoValidList {
+            player_counts: invalid,
+        });
+    }
+    if !unverified.is_empty() {
+        lints.push(Lint::TooManySetups {
+            player_counts: unverified,
+        });
+    }
+
+    for c in characters {
+        let conditions = c.conditions.iter().flatten();
+        let mut missing: Vec<String> = Vec::new();
+        for player_count in table.player_counts() {
+            // Only the closest way to satisfy the conditions is reported,
+            // nothing is missing if any of them only requires characters on the script
+            let closest = condition::alternatives(conditions.clone(), player_count)
+                .into_iter()
+                .map(|alternative| missing_characters(&alternative, characters))
+                .min_by_key(Vec::len);
+            for required in closest.into_iter().flatten() {
+                if !missing.contains(&required) {
+                    missing.push(required);
+                }
+            }
+        }
+        for required in missing {
+            lints.push(Lint::MissingCharacter {
+                character: c.id(),
+                required,
+            });
+        }
+        for condition in conditions.flat_map(Condition::unsupported_negations) {
+            lints.push(Lint::UnsupportedNegation {
+                character: c.id(),
+                condition: serde_json::to_string(condition).unwrap(),
+            });
+        }
+    }
+
+    for jinx in jinxes {
+        let [a, b] = jinx
+            .characters
+            .each_ref()
+            .map(|id| characters.iter().find(|c| c.has_id(id)));
+        if let (Some(a), Some(b)) = (a, b) {
+            lints.push(Lint::Jinx {
+                characters: [a.id(), b.id()],
+                description: jinx.description.clone(),
+            });
+        }
+    }
+
+    lints
+}
+
+/// ids of the characters that the alternative requires and that are not on the script
+fn missing_characters(alternative: &[Condition], characters: &[&Character]) -> Vec<String> {
+    alternative
+        .iter()
+        .filter_map(|condition| match condition {
+            Condition::Character { character }
+                if !characters.iter().any(|c| c.has_id(character)) =>
+            {
+                Some(character.clone())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logic::data::IncludedData;
+
+    fn script(counts: [usize; 4]) -> Vec<Character> {
+        PLAYER_TYPES
+            .into_iter()
+            .zip(counts)
+            .flat_map(|(r#type, count)| {
+                (1..=count).map(move |i| Character::test(&format!("{type:?} {i}"), r#type))
+            })
+            .collect()
+    }
+
+    fn table() -> DistributionTable {
+        DistributionTable::parse("Test", "5: 0/1/1\n6: 1/1/1\n7: 0/2/1").unwrap()
+    }
+
+    fn lint(script: &[Character], jinxes: &[Jinx]) -> Vec<Lint> {
+        let characters: Vec<&Character> = script.iter().collect();
+        lint_script(&characters, &table(), jinxes)
+    }
+
+    #[test]
+    fn full_script_has_no_lints() {
+        assert_eq!(lint(&script([13, 4, 4, 4]), &[]), []);
+    }
+
+    #[test]
+    fn reports_few_characters_without_valid_lists() {
+        let lints = lint(&script([13, 4, 0, 0]), &[]);
+        assert_eq!(
+            lints,
+            [
+                Lint::FewCharacters {
+                    r#type: Type::Minion,
+                    count: 0,
+                    usual: 4,
+                },
+                Lint::FewCharacters {
+                    r#type: Type::Demon,
+                    count: 0,
+                    usual: 4,
+                },
+                Lint::NoDemon,
+                Lint::NoValidList {
+                    player_counts: vec![5, 6, 7],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_no_valid_list_for_some_player_counts() {
+        let lints = lint(&script([13, 0, 4, 4]), &[]);
+        assert!(lints.contains(&Lint::NoValidList {
+            player_counts: vec![6],
+        }));
+    }
+
+    #[test]
+    fn reports_missing_characters() {
+        let mut script = script([13, 4, 4, 4]);
+        script[0].conditions = Some(vec![Condition::Character {
+            character: "king".to_string(),
+        }]);
+        assert_eq!(
+            lint(&script, &[]),
+            [Lint::MissingCharacter {
+                character: "townsfolk_1".to_string(),
+                required: "king".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn reports_missing_characters_in_nested_conditions() {
+        let mut script = script([13, 4, 4, 4]);
+        script[0].conditions = Some(
+            serde_json::from_str(
+                r#"[
+                    {"any_of": [{"character": "king"}, {"character": "townsfolk_2"}]},
+                    {"any_of": [
+                        {"all_of": [{"character": "king"}, {"character": "knight"}]},
+                        {"all_of": [{"character": "townsfolk_3"}, {"character": "lunatic"}]}
+                    ]},
+                    {"min": 7, "then": [{"all_of": [{"character": "damsel"}]}]},
+                    {"not": {"character": "imp"}},
+                    {"excludes": "spy"}
+                ]"#,
+            )
+            .unwrap(),
+        );
+        let missing: Vec<String> = lint(&script, &[])
+            .into_iter()
+            .filter_map(|lint| match lint {
+                Lint::MissingCharacter { required, .. } => Some(required),
+                _ => None,
+            })
+            .collect();
+        // townsfolk_2 is on the script, so king isn't required
+        assert_eq!(missing, ["lunatic", "damsel"]);
+    }
+
+    #[test]
+    fn reports_conditions_that_cant_be_negated() {
+        let mut script = script([13, 4, 4, 4]);
+        script[0].conditions = Some(
+            serde_json::from_str(
+                r#"[
+                    {"not": {"any_of": [{"character": "demon_1"}, {"extra_characters": 1}]}},
+                    {"not": {"not": {"type": "OUTSIDER", "amount": "ANY"}}}
+                ]"#,
+            )
+            .unwrap(),
+        );
+        assert_eq!(
+            lint(&script, &[]),
+            [Lint::UnsupportedNegation {
+                character: "townsfolk_1".to_string(),
+                condition: r#"{"extra_characters":1}"#.to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn reports_jinxes_on_the_script() {
+        let jinxes = [
+            Jinx {
+                characters: ["townsfolk_1".to_string(), "demon_1".to_string()],
+                description: "Jinxed".to_string(),
+            },
+            Jinx {
+                characters: ["townsfolk_1".to_string(), "homebrew".to_string()],
+                description: "Not on the script".to_string(),
+            },
+        ];
+        assert_eq!(
+            lint(&script([13, 4, 4, 4]), &jinxes),
+            [Lint::Jinx {
+                characters: ["townsfolk_1".to_string(), "demon_1".to_string()],
+                description: "Jinxed".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn reports_official_jinxes() {
+        let data: IncludedData = serde_json::from_str(include_str!("../data.json")).unwrap();
+        let script: Vec<&Character> = data
+            .characters
+            .iter()
+            .filter(|c| ["legion", "engineer", "imp"].contains(&c.id().as_str()))
+            .collect();
+        let jinxed: Vec<[String; 2]> = lint_script(&script, &table(), &data.jinxes)
+            .into_iter()
+            .filter_map(|lint| match lint {
+                Lint::Jinx { characters, .. } => Some(characters),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(jinxed, [["engineer".to_string(), "legion".to_string()]]);
+    }
+
+    #[test]
+    fn reports_scripts_with_too_many_setups() {
+        let mut script = script([13, 4, 4, 4]);
+        script.extend((0..70).map(|i| {
+            Character::test(&format!("Homebrew {i}"), Type::Townsfolk)
+                .with_conditions(r#"[{"extra_characters": 0}]"#)
+        }));
+        assert_eq!(
+            lint(&script, &[]),
+            [Lint::TooManySetups {
+                player_counts: vec![5, 6, 7],
+            }]
+        );
+    }
+}
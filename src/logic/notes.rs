@@ -0,0 +1,173 @@
+use super::{character::Character, condition::Condition, i18n::Language};
+
+/// Something the storyteller has to keep in mind while setting up the game,
+/// in the language it was created for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetupNote {
+    pub character: String,
+    pub note: String,
+}
+
+/// Notes from the data of the selected characters,
+/// followed by notes generated from their conditions and the rest of the script.
+pub fn setup_notes(
+    selected: &[&Character],
+    script: &[&Character],
+    player_count: u8,
+    language: Language,
+) -> Vec<SetupNote> {
+    let mut notes = Vec::new();
+    let mut note = |c: &Character, note: String| {
+        notes.push(SetupNote {
+            character: language.name(c).to_string(),
+            note,
+        })
+    };
+
+    for &c in selected {
+        if let Some(setup_note) = language.setup_note(c) {
+            note(c, setup_note.to_string());
+        }
+    }
+
+    for &c in selected {
+        for &r#type in &c.spares {
+            let spares: Vec<&str> = script
+                .iter()
+                .filter(|s| s.r#type == r#type && !selected.iter().any(|c| c.id() == s.id()))
+                .map(|&s| language.name(s))
+                .collect();
+            let r#type = language.t(r#type.plural_str());
+            if spares.is_empty() {
+                note(
+                    c,
+                    language.tf("No spare {type} on the script", &[("type", &r#type)]),
+                );
+            } else {
+                note(
+                    c,
+                    language.tf(
+                        "{count} spare {type} on the script: {spares}",
+                        &[
+                            ("count", &spares.len()),
+                            ("type", &r#type),
+                            ("spares", &spares.join(", ")),
+                        ],
+                    ),
+                );
+            }
+        }
+
+        for condition in c.conditions.iter().flatten() {
+            match condition {
+                Condition::Replace {
+                    replace,
+                    with,
+                    amount,
+                } => note(
+                    c,
+                    language.tf(
+                        "{replace} are not in the bag, add {amount} more of the {with} for each of them",
+                        &[
+                            ("replace", &language.t(replace.plural_str())),
+                            ("amount", amount),
+                            ("with", &language.t(with.plural_str())),
+                        ],
+                    ),
+                ),
+                Condition::ChosenAfterSetup { chosen_after_setup } => note(
+                    c,
+                    language.tf(
+                        "{type} are not in the bag, they are chosen after setup",
+                        &[("type", &language.t(chosen_after_setup.plural_str()))],
+                    ),
+                ),
+                Condition::Seats { seats } => note(
+                    c,
+                    language.tf(
+                        "Put {count} {character} tokens in the bag",
+                        &[
+                            ("count", &seats.count(player_count)),
+                            ("character", &language.name(c)),
+                        ],
+                    ),
+                ),
+                _ => {}
+            }
+        }
+    }
+
+    notes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logic::character::Type;
+
+    fn notes(selected: &[&Character], script: &[&Character], language: Language) -> Vec<String> {
+        setup_notes(selected, script, 10, language)
+            .into_iter()
+            .map(|n| format!("{}: {}", n.character, n.note))
+            .collect()
+    }
+
+    #[test]
+    fn notes_from_the_data_come_first() {
+        let drunk = Character {
+            setup_note: Some("Add a Townsfolk token to the bag".to_string()),
+            ..Character::test("Drunk", Type::Outsider)
+        };
+        let legion = Character::test("Legion", Type::Demon)
+            .with_conditions(r#"[{"seats": "MAJORITY"}, {"chosen_after_setup": "MINION"}]"#);
+        let selected = [&legion, &drunk];
+        assert_eq!(
+            notes(&selected, &selected, Language::English),
+            [
+                "Drunk: Add a Townsfolk token to the bag",
+                "Legion: Put 6 Legion tokens in the bag",
+                "Legion: Minions are not in the bag, they are chosen after setup",
+            ]
+        );
+    }
+
+    #[test]
+    fn notes_list_the_spare_characters_on_the_script() {
+        let spares = |name, r#type| Character {
+            spares: vec![r#type],
+            ..Character::test(name, r#type)
+        };
+        let alchemist = spares("Alchemist", Type::Townsfolk);
+        let bounty_hunter = spares("Bounty Hunter", Type::Demon);
+        let chef = Character::test("Chef", Type::Townsfolk);
+        let empath = Character::test("Empath", Type::Townsfolk);
+        let imp = Character::test("Imp", Type::Demon);
+        let script = [&alchemist, &bounty_hunter, &chef, &empath, &imp];
+
+        assert_eq!(
+            notes(
+                &[&alchemist, &chef, &bounty_hunter, &imp],
+                &script,
+                Language::English
+            ),
+            [
+                "Alchemist: 1 spare Townsfolk on the script: Empath",
+                "Bounty Hunter: No spare Demons on the script",
+            ]
+        );
+        assert_eq!(
+            notes(&[&alchemist], &script, Language::German),
+            ["Alchemist: 2 übrige Dorfbewohner auf dem Skript: Koch, Empathin"]
+        );
+    }
+
+    #[test]
+    fn notes_explain_replaced_types() {
+        let lil_monsta = Character::test("Lil' Monsta", Type::Demon)
+            .with_conditions(r#"[{"replace": "DEMON", "with": "MINION", "amount": 1}]"#);
+        assert_eq!(
+            notes(&[&lil_monsta], &[&lil_monsta], Language::English),
+            ["Lil' Monsta: Demons are not in the bag, add 1 more of the Minions for each of them"]
+        );
+    }
+}
@@ -0,0 +1,15 @@
+use qrcode::{EcLevel, QrCode, render::svg};
+
+/// Size in px the QR code is rendered at, phones scan it fine from a screen at this size.
+const SIZE: u32 = 320;
+
+/// Renders `data` as an SVG QR code.
+/// The lowest error correction is used, as the code is only shown on screens
+/// and it fits the most data, e.g. a whole script export.
+pub fn qr_svg(data: &str) -> Result<String, String> {
+    let code = QrCode::with_error_correction_level(data, EcLevel::L).map_err(|e| e.to_string())?;
+    Ok(code
+        .render::<svg::Color>()
+        .min_dimensions(SIZE, SIZE)
+        .build())
+}
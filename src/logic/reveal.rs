@@ -0,0 +1,357 @@
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use rand::{
+    Rng,
+    seq::{IteratorRandom as _, SliceRandom as _},
+};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    character::{Character, Type},
+    seating,
+};
+
+/// Key the payload is XORed with, so that the character can't be read from the link at a glance.
+/// This is obfuscation, not encryption.
+const KEY: &[u8] = b"clocktower";
+
+/// Minions and the Demon only learn each other with this many players or more.
+const MIN_PLAYERS_FOR_EVIL_INFO: u8 = 7;
+
+/// What a single player gets to see about their own character.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Reveal {
+    /// seat number, starting at 1
+    pub seat: u8,
+    pub character: String,
+    /// name of the character, for characters the viewer doesn't know
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub evil: Option<EvilInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EvilInfo {
+    /// seats of the Demons
+    pub demons: Vec<u8>,
+    /// seats of the other Minions
+    pub minions: Vec<u8>,
+    /// seats of the players who think they are evil but aren't, only shown to the Demon
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disguised: Vec<u8>,
+    /// not-in-play characters the Demon can bluff as, only shown to the Demon
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bluffs: Vec<String>,
+}
+
+/// What a player who doesn't know their character is shown instead.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Disguise {
+    /// a not-in-play character of the type, e.g. the Drunk's Townsfolk
+    NotInPlay(Type),
+    /// the in-play character of the type with made-up evil info, e.g. the Lunatic's Demon
+    InPlay(Type),
+    /// the token that is left over when seating, e.g. the Marionette's Townsfolk
+    Surplus,
+}
+
+/// One reveal per seat, showing each player the token they receive.
+/// Disguised players get no real evil info and are only listed to the Demons,
+/// the good players who think they are evil separately from the Minions.
+/// The not-in-play characters of disguises and the made-up evil info are chosen with `rng`.
+pub fn reveals<R: Rng + ?Sized>(
+    seats: &[&Character],
+    in_play: &[&Character],
+    script: &[&Character],
+    bluffs: &[String],
+    player_count: u8,
+    rng: &mut R,
+) -> Vec<Reveal> {
+    let evil_info = player_count >= MIN_PLAYERS_FOR_EVIL_INFO;
+    let seats_of = |r#type: Type, except: usize, disguised: bool| -> Vec<u8> {
+        (0..seats.len())
+            .filter(|&i| i != except && seats[i].r#type == r#type)
+            .filter(|&i| disguised || seats[i].disguise.is_none())
+            .map(|i| i as u8 + 1)
+            .collect()
+    };
+    let mut not_in_play: Vec<&Character> = script
+        .iter()
+        .copied()
+        .filter(|c| !in_play.iter().any(|p| p.id() == c.id()) && !bluffs.contains(&c.id()))
+        .collect();
+    let mut take_not_in_play = |rng: &mut R, filter: &dyn Fn(&Character) -> bool| {
+        let i = (0..not_in_play.len())
+            .filter(|&i| filter(not_in_play[i]))
+            .choose(rng)?;
+        Some(not_in_play.remove(i))
+    };
+    let thinks_evil = || -> Vec<u8> {
+        (0..seats.len())
+            .filter(|&i| {
+                seats[i].r#type.is_good()
+                    && matches!(seats[i].disguise, Some(Disguise::InPlay(t)) if !t.is_good())
+            })
+            .map(|i| i as u8 + 1)
+            .collect()
+    };
+    let mut surplus = seating::surplus(in_play, player_count).into_iter();
+
+    let mut reveals = Vec::new();
+    for (i, c) in seats.iter().enumerate() {
+        let seat = i as u8 + 1;
+        let Some(disguise) = c.disguise else {
+            let evil = (evil_info && !c.r#type.is_good()).then(|| EvilInfo {
+                demons: seats_of(Type::Demon, i, false),
+                // Only the Demon knows the disguised Minions
+                minions: seats_of(Type::Minion, i, c.r#type == Type::Demon),
+                disguised: if c.r#type == Type::Demon {
+                    thinks_evil()
+                } else {
+                    Vec::new()
+                },
+                bluffs: if c.r#type == Type::Demon {
+                    bluffs.to_vec()
+                } else {
+                    Vec::new()
+                },
+            });
+            reveals.push(Reveal {
+                seat,
+                character: c.id(),
+                name: c.name.clone(),
+                evil,
+            });
+            continue;
+        };
+
+        let (character, shown) = match disguise {
+            Disguise::NotInPlay(r#type) => (take_not_in_play(rng, &|c| c.r#type == r#type), r#type),
+            Disguise::InPlay(r#type) => (
+                seats
+                    .iter()
+                    .copied()
+                    .filter(|c| c.r#type == r#type)
+                    .choose(rng)
+                    .or_else(|| take_not_in_play(rng, &|c| c.r#type == r#type)),
+                r#type,
+            ),
+            Disguise::Surplus => match surplus.next() {
+                Some(s) => (Some(s), s.r#type),
+                None => (
+                    take_not_in_play(rng, &|c| c.r#type == Type::Townsfolk),
+                    Type::Townsfolk,
+                ),
+            },
+        };
+        // Players who think they are evil get made-up info like a real player of the type
+        let evil = (evil_info && !shown.is_good()).then(|| {
+            let count = |r#type: Type| {
+                seats_of(r#type, usize::MAX, true)
+                    .len()
+                    .saturating_sub((shown == r#type) as usize)
+            };
+            let (demons, minions) = (count(Type::Demon), count(Type::Minion));
+            let mut others = (1..=seats.len() as u8)
+                .filter(|&s| s != seat)
+                .choose_multiple(rng, demons + minions);
+            others.shuffle(rng);
+            let mut minions = others.split_off(demons.min(others.len()));
+            others.sort_unstable();
+            minions.sort_unstable();
+            let bluffs = if shown == Type::Demon {
+                (0..bluffs.len())
+                    .filter_map(|_| take_not_in_play(rng, &|c| c.r#type.is_good()))
+                    .map(Character::id)
+                    .collect()
+            } else {
+                Vec::new()
+            };
+            EvilInfo {
+                demons: others,
+                minions,
+                disguised: Vec::new(),
+                bluffs,
+            }
+        });
+        // Without a character to show, the player sees their own
+        let character = character.unwrap_or(c);
+        reveals.push(Reveal {
+            seat,
+            character: character.id(),
+            name: character.name.clone(),
+            evil,
+        });
+    }
+    reveals
+}
+
+pub fn encode(reveal: &Reveal) -> String {
+    let json = serde_json::to_vec(reveal).unwrap();
+    URL_SAFE_NO_PAD.encode(xor(json))
+}
+
+pub fn decode(token: &str) -> Result<Reveal, String> {
+    let bytes = URL_SAFE_NO_PAD
+        .decode(token.trim())
+        .map_err(|e| e.to_string())?;
+    serde_json::from_slice(&xor(bytes)).map_err(|e| e.to_string())
+}
+
+fn xor(mut bytes: Vec<u8>) -> Vec<u8> {
+    for (b, k) in bytes.iter_mut().zip(KEY.iter().cycle()) {
+        *b ^= k;
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{SeedableRng as _, rngs::StdRng};
+
+    use super::*;
+
+    fn townsfolk(range: std::ops::RangeInclusive<usize>) -> Vec<Character> {
+        range
+            .map(|i| Character::test(&format!("Townsfolk {i}"), Type::Townsfolk))
+            .collect()
+    }
+
+    /// The reveals of the seats in order, with the given characters on the script.
+    fn reveal(seats: &[&Character], in_play: &[&Character], script: &[&Character]) -> Vec<Reveal> {
+        let bluffs = ["townsfolk_9".to_string()];
+        let player_count = seats.len() as u8;
+        reveals(
+            seats,
+            in_play,
+            script,
+            &bluffs,
+            player_count,
+            &mut StdRng::seed_from_u64(0),
+        )
+    }
+
+    #[test]
+    fn drunk_sees_a_not_in_play_townsfolk() {
+        let townsfolk = townsfolk(1..=9);
+        let drunk = Character::test("Drunk", Type::Outsider)
+            .with_disguise(Disguise::NotInPlay(Type::Townsfolk));
+        let imp = Character::test("Imp", Type::Demon);
+        let seats: Vec<&Character> = [&drunk, &imp].into_iter().chain(&townsfolk[..3]).collect();
+        let script: Vec<&Character> = seats.iter().copied().chain(&townsfolk[3..]).collect();
+
+        let reveals = reveal(&seats, &seats, &script);
+        let not_in_play = [
+            "townsfolk_4",
+            "townsfolk_5",
+            "townsfolk_6",
+            "townsfolk_7",
+            "townsfolk_8",
+        ];
+        assert!(not_in_play.contains(&reveals[0].character.as_str()));
+        assert_eq!(reveals[0].evil, None);
+        assert_eq!(reveals[1].character, "imp");
+        assert_eq!(reveals[2].character, "townsfolk_1");
+    }
+
+    #[test]
+    fn lunatic_sees_a_demon_with_made_up_evil_info() {
+        let townsfolk = townsfolk(1..=9);
+        let lunatic =
+            Character::test("Lunatic", Type::Outsider).with_disguise(Disguise::InPlay(Type::Demon));
+        let imp = Character::test("Imp", Type::Demon);
+        let poisoner = Character::test("Poisoner", Type::Minion);
+        let seats: Vec<&Character> = [&lunatic, &imp, &poisoner]
+            .into_iter()
+            .chain(&townsfolk[..4])
+            .collect();
+        let script: Vec<&Character> = seats.iter().copied().chain(&townsfolk[4..]).collect();
+
+        let reveals = reveal(&seats, &seats, &script);
+        assert_eq!(reveals[0].character, "imp");
+        let evil = reveals[0].evil.as_ref().unwrap();
+        assert!(evil.demons.is_empty());
+        assert_eq!(evil.minions.len(), 1);
+        assert!(!evil.minions.contains(&1));
+        assert_eq!(evil.bluffs.len(), 1);
+        assert_ne!(evil.bluffs, ["townsfolk_9"]);
+
+        // Only the Demon learns who the Lunatic is, apart from its Minions
+        let imp = reveals[1].evil.as_ref().unwrap();
+        assert_eq!(
+            (
+                imp.demons.as_slice(),
+                imp.minions.as_slice(),
+                imp.disguised.as_slice()
+            ),
+            (&[][..], &[3][..], &[1][..])
+        );
+        assert_eq!(imp.bluffs, ["townsfolk_9"]);
+        let poisoner = reveals[2].evil.as_ref().unwrap();
+        assert_eq!(poisoner.demons, [2]);
+        assert!(poisoner.disguised.is_empty());
+    }
+
+    #[test]
+    fn marionette_sees_the_surplus_townsfolk() {
+        let townsfolk = townsfolk(1..=5);
+        let marionette = Character::test("Marionette", Type::Minion)
+            .with_conditions(r#"[{"extra_characters": ["MINION", 1]}]"#)
+            .with_disguise(Disguise::Surplus);
+        let spy = Character::test("Spy", Type::Minion);
+        let imp = Character::test("Imp", Type::Demon);
+        let in_play: Vec<&Character> = [&marionette, &spy, &imp]
+            .into_iter()
+            .chain(&townsfolk)
+            .collect();
+        let seats = seating::seated(&in_play, 7);
+        assert_eq!(seats.len(), 7);
+
+        let reveals = reveal(&seats, &in_play, &in_play);
+        assert_eq!(reveals[0].character, "townsfolk_5");
+        assert_eq!(reveals[0].evil, None);
+        // Only the Demon knows who the Marionette is
+        assert!(reveals[1].evil.as_ref().unwrap().minions.is_empty());
+        assert_eq!(reveals[2].evil.as_ref().unwrap().minions, [1, 2]);
+    }
+
+    #[test]
+    fn reveals_survive_encoding() {
+        let reveal = Reveal {
+            seat: 3,
+            character: "imp".to_string(),
+            name: "Imp".to_string(),
+            evil: Some(EvilInfo {
+                demons: Vec::new(),
+                minions: vec![1, 5],
+                disguised: vec![2],
+                bluffs: vec!["chef".to_string()],
+            }),
+        };
+        assert_eq!(decode(&encode(&reveal)), Ok(reveal));
+    }
+
+    #[test]
+    fn reveals_include_the_character_names() {
+        let townsfolk = townsfolk(1..=3);
+        let homebrew = Character::test("Sneaky Pete", Type::Demon);
+        let drunk = Character::test("Drunk", Type::Outsider)
+            .with_disguise(Disguise::NotInPlay(Type::Townsfolk));
+        let seats: Vec<&Character> = [&homebrew, &drunk]
+            .into_iter()
+            .chain(&townsfolk[..2])
+            .collect();
+        let script: Vec<&Character> = seats.iter().copied().chain(&townsfolk[2..]).collect();
+
+        let reveals = reveal(&seats, &seats, &script);
+        assert_eq!(
+            (reveals[0].character.as_str(), reveals[0].name.as_str()),
+            ("sneaky_pete", "Sneaky Pete")
+        );
+        // The Drunk is shown the name of the Townsfolk they think they are
+        assert_eq!(
+            (reveals[1].character.as_str(), reveals[1].name.as_str()),
+            ("townsfolk_3", "Townsfolk 3")
+        );
+    }
+}
@@ -0,0 +1,368 @@
+use rand::{Rng, seq::SliceRandom};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    character::{Character, Type},
+    condition::Condition,
+    i18n::Language,
+    state::undealt_types,
+};
+
+/// Where a character has to sit relative to the other players.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(untagged)]
+pub enum SeatingCondition {
+    /// sit next to a character of the type, or between two of them if `both` is set
+    Adjacent {
+        adjacent: Type,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        both: bool,
+    },
+    /// all players of the alignment sit in one contiguous line
+    Contiguous { contiguous: Alignment },
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Alignment {
+    Good,
+    Evil,
+}
+
+impl Alignment {
+    pub fn of(character: &Character) -> Self {
+        if character.r#type.is_good() {
+            Alignment::Good
+        } else {
+            Alignment::Evil
+        }
+    }
+
+    pub fn str(&self) -> &'static str {
+        match self {
+            Alignment::Good => "Good",
+            Alignment::Evil => "Evil",
+        }
+    }
+}
+
+/// A seating condition that isn't met, referring to the character by id.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SeatingViolation {
+    NotAdjacent {
+        character: String,
+        r#type: Type,
+        both: bool,
+    },
+    NotContiguous {
+        character: String,
+        alignment: Alignment,
+    },
+}
+
+impl SeatingViolation {
+    /// Describes the violation, `name` gives the name of a character by its id.
+    pub fn describe(&self, language: Language, name: impl Fn(&str) -> String) -> String {
+        match self {
+            SeatingViolation::NotAdjacent {
+                character,
+                r#type,
+                both,
+            } => language.tf(
+                if *both {
+                    "{character} has to sit between two {type}"
+                } else {
+                    "{character} has to sit next to one of the {type}"
+                },
+                &[
+                    ("character", &name(character)),
+                    ("type", &language.t(r#type.plural_str())),
+                ],
+            ),
+            SeatingViolation::NotContiguous {
+                character,
+                alignment,
+            } => language.tf(
+                "{alignment} players have to sit in one line for the {character}",
+                &[
+                    ("alignment", &language.t(alignment.str())),
+                    ("character", &name(character)),
+                ],
+            ),
+        }
+    }
+}
+
+/// The characters that are dealt to the players, one per seat.
+/// Characters of undealt types are left out and characters that are dealt to several players
+/// are repeated.
+pub fn seated<'a>(characters: &[&'a Character], player_count: u8) -> Vec<&'a Character> {
+    split_surplus(characters, player_count).0
+}
+
+/// Tokens that are left over when seating, they are shown to players who don't know their
+/// character (e.g. the Marionette's Townsfolk). Townsfolk without seating conditions
+/// are left over first.
+pub fn surplus<'a>(characters: &[&'a Character], player_count: u8) -> Vec<&'a Character> {
+    split_surplus(characters, player_count).1
+}
+
+/// How many players the character is dealt to, e.g. the Legion to most of them.
+pub fn seat_count(character: &Character, player_count: u8) -> u8 {
+    character
+        .conditions
+        .iter()
+        .flatten()
+        .find_map(|cond| match cond {
+            Condition::Seats { seats } => Some(seats.count(player_count)),
+            _ => None,
+        })
+        .unwrap_or(1)
+}
+
+fn split_surplus<'a>(
+    characters: &[&'a Character],
+    player_count: u8,
+) -> (Vec<&'a Character>, Vec<&'a Character>) {
+    let undealt = undealt_types(characters);
+    let mut seated = Vec::new();
+    for &c in characters {
+        if undealt.contains(&c.r#type) {
+            continue;
+        }
+        seated.extend(std::iter::repeat_n(c, seat_count(c, player_count) as usize));
+    }
+
+    let mut surplus = Vec::new();
+    while seated.len() > player_count as usize {
+        let i = seated
+            .iter()
+            .rposition(|c| c.r#type == Type::Townsfolk && c.seating.is_empty())
+            .unwrap_or(seated.len() - 1);
+        surplus.push(seated.remove(i));
+    }
+    (seated, surplus)
+}
+
+/// Every seating condition that isn't met. Seats are in clockwise order around a circle.
+/// Players only have to sit next to as many characters of a type as are seated,
+/// e.g. the Marionette doesn't need a Demon if the Lil' Monsta is in play.
+pub fn violations(seats: &[&Character]) -> Vec<SeatingViolation> {
+    let n = seats.len();
+    let mut violations = Vec::new();
+    for (i, c) in seats.iter().enumerate() {
+        for condition in &c.seating {
+            match *condition {
+                SeatingCondition::Adjacent { adjacent, both } => {
+                    let neighbours = if n < 2 {
+                        0
+                    } else if n == 2 {
+                        (seats[(i + 1) % n].r#type == adjacent) as usize
+                    } else {
+                        [seats[(i + n - 1) % n], seats[(i + 1) % n]]
+                            .iter()
+                            .filter(|c| c.r#type == adjacent)
+                            .count()
+                    };
+                    let others = (0..n)
+                        .filter(|&j| j != i && seats[j].r#type == adjacent)
+                        .count();
+                    let required = if both { 2 } else { 1 };
+                    if neighbours < required.min(others).min(n.min(3).saturating_sub(1)) {
+                        violations.push(SeatingViolation::NotAdjacent {
+                            character: c.id(),
+                            r#type: adjacent,
+                            both,
+                        });
+                    }
+                }
+                SeatingCondition::Contiguous { contiguous } => {
+                    let is_aligned = |i: usize| Alignment::of(seats[i % n]) == contiguous;
+                    // A line starts wherever an aligned player doesn't have one to the left
+                    let lines = (0..n)
+                        .filter(|&i| is_aligned(i) && !is_aligned(i + n - 1))
+                        .count();
+                    if lines > 1 {
+                        violations.push(SeatingViolation::NotContiguous {
+                            character: c.id(),
+                            alignment: contiguous,
+                        });
+                    }
+                }
+            }
+        }
+    }
+    violations
+}
+
+/// Shuffles the characters into seats until every seating condition is met.
+/// Returns the amount of tries needed and the seats.
+pub fn deal<'a, R: Rng + ?Sized>(
+    characters: &[&'a Character],
+    rng: &mut R,
+) -> Option<(usize, Vec<&'a Character>)> {
+    let mut seats = characters.to_vec();
+    for i in 0..crate::consts::MAX_GENERATION_ITERATIONS {
+        seats.shuffle(rng);
+        if violations(&seats).is_empty() {
+            return Some((i + 1, seats));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{SeedableRng as _, rngs::StdRng};
+
+    use super::*;
+
+    fn seating(name: &str, r#type: Type, seating: SeatingCondition) -> Character {
+        Character {
+            seating: vec![seating],
+            ..Character::test(name, r#type)
+        }
+    }
+
+    fn marionette() -> Character {
+        let adjacent = SeatingCondition::Adjacent {
+            adjacent: Type::Demon,
+            both: false,
+        };
+        seating("Marionette", Type::Minion, adjacent)
+    }
+
+    fn townsfolk(count: usize) -> Vec<Character> {
+        (1..=count)
+            .map(|i| Character::test(&format!("Townsfolk {i}"), Type::Townsfolk))
+            .collect()
+    }
+
+    #[test]
+    fn adjacent_to_a_type() {
+        let [t1, t2] = townsfolk(2).try_into().unwrap();
+        let marionette = marionette();
+        let demon = Character::test("Demon", Type::Demon);
+        assert_eq!(violations(&[&marionette, &demon, &t1, &t2]), []);
+        assert_eq!(violations(&[&t1, &t2, &demon, &marionette]), []);
+        assert_eq!(
+            violations(&[&marionette, &t1, &demon, &t2]),
+            [SeatingViolation::NotAdjacent {
+                character: "marionette".to_string(),
+                r#type: Type::Demon,
+                both: false,
+            }]
+        );
+    }
+
+    #[test]
+    fn adjacent_on_both_sides() {
+        let [t1, t2] = townsfolk(2).try_into().unwrap();
+        let adjacent = SeatingCondition::Adjacent {
+            adjacent: Type::Townsfolk,
+            both: true,
+        };
+        let between = seating("Between", Type::Outsider, adjacent);
+        let demon = Character::test("Demon", Type::Demon);
+        assert_eq!(violations(&[&t1, &between, &t2, &demon]), []);
+        assert_eq!(
+            violations(&[&t1, &between, &demon, &t2]),
+            [SeatingViolation::NotAdjacent {
+                character: "between".to_string(),
+                r#type: Type::Townsfolk,
+                both: true,
+            }]
+        );
+        // With a single Townsfolk seated, sitting next to it is enough
+        assert_eq!(violations(&[&t1, &between, &demon]), []);
+        assert_eq!(violations(&[&between, &demon, &demon, &t1]), []);
+        assert_eq!(violations(&[&between, &demon, &t1, &demon]).len(), 1);
+    }
+
+    #[test]
+    fn contiguous_alignment() {
+        let [t1, t2] = townsfolk(2).try_into().unwrap();
+        let contiguous = SeatingCondition::Contiguous {
+            contiguous: Alignment::Evil,
+        };
+        let minion = seating("Minion", Type::Minion, contiguous);
+        let demon = Character::test("Demon", Type::Demon);
+        assert_eq!(violations(&[&t1, &minion, &demon, &t2]), []);
+        // The line can wrap around the circle
+        assert_eq!(violations(&[&demon, &t1, &t2, &minion]), []);
+        assert_eq!(
+            violations(&[&minion, &t1, &demon, &t2]),
+            [SeatingViolation::NotContiguous {
+                character: "minion".to_string(),
+                alignment: Alignment::Evil,
+            }]
+        );
+    }
+
+    #[test]
+    fn no_seated_character_of_the_type() {
+        let townsfolk = townsfolk(3);
+        let marionette = marionette();
+        let lil_monsta = Character::test("Lil' Monsta", Type::Demon)
+            .with_conditions(r#"[{"replace": "DEMON", "with": "MINION", "amount": 1}]"#);
+        let mut characters: Vec<&Character> = townsfolk.iter().collect();
+        characters.extend([&marionette, &lil_monsta]);
+
+        let seats = seated(&characters, 4);
+        assert!(!seats.iter().any(|c| c.r#type == Type::Demon));
+        assert_eq!(violations(&seats), []);
+        let (tries, _) = deal(&seats, &mut StdRng::seed_from_u64(0)).unwrap();
+        assert_eq!(tries, 1);
+    }
+
+    #[test]
+    fn deal_meets_the_seating_conditions() {
+        let townsfolk = townsfolk(6);
+        let marionette = marionette();
+        let demon = Character::test("Demon", Type::Demon);
+        let mut characters: Vec<&Character> = townsfolk.iter().collect();
+        characters.extend([&marionette, &demon]);
+
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..20 {
+            let (_, seats) = deal(&characters, &mut rng).unwrap();
+            assert_eq!(violations(&seats), []);
+            let mut sorted = seats.clone();
+            sorted.sort();
+            let mut expected = characters.clone();
+            expected.sort();
+            assert_eq!(sorted, expected);
+        }
+    }
+
+    #[test]
+    fn surplus_characters() {
+        let townsfolk = townsfolk(3);
+        let marionette = marionette();
+        let legion = Character::test("Legion", Type::Demon).with_conditions(r#"[{"seats": 2}]"#);
+        let mut characters: Vec<&Character> = townsfolk.iter().collect();
+        characters.extend([&marionette, &legion]);
+
+        // Legion takes two seats, so two of the Townsfolk are left over
+        let (seated, surplus) = split_surplus(&characters, 4);
+        let ids = |characters: &[&Character]| characters.iter().map(|c| c.id()).collect::<Vec<_>>();
+        assert_eq!(
+            ids(&seated),
+            ["townsfolk_1", "marionette", "legion", "legion"]
+        );
+        assert_eq!(ids(&surplus), ["townsfolk_3", "townsfolk_2"]);
+        assert_eq!(super::seated(&characters, 4), seated);
+        assert_eq!(super::surplus(&characters, 4), surplus);
+
+        // Townsfolk with seating conditions are kept
+        let seated_townsfolk = seating(
+            "Seated",
+            Type::Townsfolk,
+            SeatingCondition::Contiguous {
+                contiguous: Alignment::Good,
+            },
+        );
+        let characters = [&seated_townsfolk, &townsfolk[0], &legion];
+        assert_eq!(ids(&super::surplus(&characters, 3)), ["townsfolk_1"]);
+    }
+}
@@ -1,11 +1,25 @@
-use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet, HashMap},
+    ops::RangeInclusive,
+    rc::Rc,
+};
 
-use rand::prelude::IndexedRandom as _;
+use rand::{Rng, SeedableRng as _, rngs::StdRng, seq::IteratorRandom as _};
 
 use super::{
+    analysis::{self, Feasibility, Pool, Setup, feasibility},
+    balance::Balance,
     character::{Character, Type},
-    condition::{Condition, ExtraCharacters, TypeCond},
-    data::{IncludedData, Script, UserData},
+    condition::{self, Condition, ExtraCharacters, TypeCond},
+    data::{ImportError, IncludedData, Script, ScriptImport, UserData},
+    distribution::DistributionTable,
+    editor::ScriptEditor,
+    lint::{Lint, lint_script},
+    notes::{SetupNote, setup_notes},
+    reveal::{self, Reveal},
+    seating,
+    statistics::Statistics,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -14,14 +28,75 @@ pub struct State {
     pub selected: BTreeMap<String, Selected>,
     pub player_count: u8,
     pub type_counts_locked: bool,
-    pub outsider_count: u8,
-    pub minion_count: u8,
-    pub demon_count: u8,
+    pub outsider_count: RangeInclusive<u8>,
+    pub minion_count: RangeInclusive<u8>,
+    pub demon_count: RangeInclusive<u8>,
     pub included_data: IncludedData,
     pub user_data: UserData,
     pub expanded_script_menu: bool,
     pub script_rename_input: String,
     pub script_input: String,
+    pub pending_imports: Vec<PendingImport>,
+    pub script_editor: Option<ScriptEditor>,
+    pub analysis_travellers: u8,
+    pub statistics: Option<Statistics>,
+    /// only generate lists with a balance score in this range
+    pub balance_target: Option<RangeInclusive<i32>>,
+    pub expanded_tag_menu: bool,
+    pub tag_character_input: String,
+    pub tag_input: String,
+    pub distribution_name_input: String,
+    pub distribution_input: String,
+    /// character ids in clockwise seat order
+    pub seats: Vec<String>,
+    /// seat picked to be swapped with the next one that is clicked
+    pub selected_seat: Option<usize>,
+    pub character_sheet: Option<SheetScope>,
+    /// ids of the characters the Demon can bluff as, picked when dealing seats
+    pub bluffs: Vec<String>,
+    /// seed for the choices made for the reveals, so that the links stay the same
+    pub reveal_seed: u64,
+    /// the reveal page to show instead of the generator, from a player's reveal link
+    pub reveal: Option<Result<Reveal, String>>,
+    pub revealed: bool,
+    /// payload shown as a QR code in a modal
+    pub qr: Option<String>,
+    /// setups of the last generation, they only change with the script, player count,
+    /// type counts and locked characters
+    pub setups_cache: RefCell<Option<(SetupsKey, CachedSetups)>>,
+    /// whether the type counts are taken from a distribution table
+    /// that has no row for the player count
+    pub missing_distribution_row: bool,
+    /// lints of the current script, only rerun when the script changes
+    pub script_lints: Option<(ScriptKey, Vec<Lint>)>,
+    /// feasibility table of the current script for the amount of travellers it was made for
+    pub script_feasibility: Option<(ScriptKey, u8, Vec<Feasibility>)>,
+}
+
+/// Everything the checks of a script depend on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptKey {
+    characters: Vec<String>,
+    table: DistributionTable,
+}
+
+/// Setups shared with the cache, `None` if there are too many to enumerate.
+pub type CachedSetups = Rc<Option<Vec<Setup>>>;
+
+/// Everything the setups of a generation depend on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetupsKey {
+    characters: Vec<String>,
+    locked: BTreeSet<String>,
+    player_count: u8,
+    type_counts: BTreeMap<Type, BTreeSet<i8>>,
+}
+
+/// Which characters are printed on the character sheet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SheetScope {
+    Script,
+    Selected,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
@@ -29,33 +104,125 @@ pub struct Selected {
     pub locked: bool,
 }
 
+/// A parsed script (or the reason it couldn't be parsed) waiting to be confirmed by the user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingImport {
+    /// file name or a description of where the script came from
+    pub source: String,
+    pub result: Result<ScriptImport, ImportError>,
+}
+
 impl State {
-    pub fn townsfolk_count(&self) -> u8 {
-        self.player_count
-            .saturating_sub(self.outsider_count)
-            .saturating_sub(self.minion_count)
-            .saturating_sub(self.demon_count)
+    pub fn new(included_data: IncludedData, user_data: UserData) -> Self {
+        Self {
+            script: crate::consts::DEFAULT_SCRIPT.to_string(),
+            selected: BTreeMap::new(),
+            player_count: 10,
+            type_counts_locked: true,
+            outsider_count: 0..=0,
+            minion_count: 2..=2,
+            demon_count: 1..=1,
+            included_data,
+            user_data,
+            expanded_script_menu: false,
+            script_rename_input: String::new(),
+            script_input: String::new(),
+            pending_imports: Vec::new(),
+            script_editor: None,
+            analysis_travellers: 0,
+            statistics: None,
+            balance_target: None,
+            expanded_tag_menu: false,
+            tag_character_input: String::new(),
+            tag_input: String::new(),
+            distribution_name_input: String::new(),
+            distribution_input: String::new(),
+            seats: Vec::new(),
+            selected_seat: None,
+            character_sheet: None,
+            bluffs: Vec::new(),
+            reveal_seed: 0,
+            reveal: None,
+            revealed: false,
+            qr: None,
+            setups_cache: Default::default(),
+            missing_distribution_row: false,
+            script_lints: None,
+            script_feasibility: None,
+        }
     }
 
-    pub fn update_type_counts(&mut self) {
-        if !self.type_counts_locked || self.player_count < 5 {
-            return;
+    pub fn townsfolk_count(&self) -> RangeInclusive<u8> {
+        let counts = [&self.outsider_count, &self.minion_count, &self.demon_count];
+        let min = counts
+            .iter()
+            .fold(self.player_count, |acc, c| acc.saturating_sub(*c.end()));
+        let max = counts
+            .iter()
+            .fold(self.player_count, |acc, c| acc.saturating_sub(*c.start()));
+        min..=max
+    }
+
+    pub fn type_count(&self, r#type: Type) -> Option<&RangeInclusive<u8>> {
+        match r#type {
+            Type::Outsider => Some(&self.outsider_count),
+            Type::Minion => Some(&self.minion_count),
+            Type::Demon => Some(&self.demon_count),
+            _ => None,
+        }
+    }
+
+    fn type_count_mut(&mut self, r#type: Type) -> Option<&mut RangeInclusive<u8>> {
+        match r#type {
+            Type::Outsider => Some(&mut self.outsider_count),
+            Type::Minion => Some(&mut self.minion_count),
+            Type::Demon => Some(&mut self.demon_count),
+            _ => None,
+        }
+    }
+
+    pub fn set_type_count_min(&mut self, r#type: Type, min: u8) {
+        let min = min.min(crate::consts::MAX_TYPE_COUNT);
+        if let Some(count) = self.type_count_mut(r#type) {
+            *count = min..=(*count.end()).max(min);
         }
+    }
 
-        self.demon_count = 1;
+    pub fn set_type_count_max(&mut self, r#type: Type, max: u8) {
+        let max = max.min(crate::consts::MAX_TYPE_COUNT);
+        if let Some(count) = self.type_count_mut(r#type) {
+            *count = (*count.start()).min(max)..=max;
+        }
+    }
 
-        if self.player_count == 5 {
-            self.outsider_count = 0;
-            self.minion_count = 1;
-        } else if self.player_count == 6 {
-            self.outsider_count = 1;
-            self.minion_count = 1;
+    /// Clamps the player count to the distribution table and takes the type counts from it.
+    pub fn update_type_counts(&mut self) {
+        let table = self.distribution_table();
+        let range = self.player_count_range();
+        self.player_count = self.player_count.clamp(*range.start(), *range.end());
+        self.missing_distribution_row = false;
+        if !self.type_counts_locked {
+            return;
+        }
+        if let Some((outsiders, minions, demons)) = table.type_counts(self.player_count) {
+            self.outsider_count = outsiders..=outsiders;
+            self.minion_count = minions..=minions;
+            self.demon_count = demons..=demons;
         } else {
-            self.outsider_count = (self.player_count - 4) % 3;
-            self.minion_count = (self.player_count - 4) / 3;
+            self.outsider_count = 0..=0;
+            self.minion_count = 0..=0;
+            self.demon_count = 0..=0;
+            self.missing_distribution_row = true;
         }
     }
 
+    /// The player counts of the distribution table of the current script.
+    pub fn player_count_range(&self) -> RangeInclusive<u8> {
+        self.distribution_table()
+            .player_count_range()
+            .unwrap_or(crate::consts::MIN_PLAYER_COUNT..=crate::consts::MAX_PLAYER_COUNT)
+    }
+
     pub fn selected_characters(&self) -> Vec<&Character> {
         self.selected
             .keys()
@@ -75,15 +242,133 @@ impl State {
             .collect()
     }
 
-    fn characters(&self) -> impl Iterator<Item = &Character> {
+    pub fn characters(&self) -> impl Iterator<Item = &Character> {
         self.included_data
             .characters
             .iter()
             .chain(self.user_data.characters.iter())
     }
 
-    fn get_character(&self, id: &str) -> Option<&Character> {
-        self.characters().find(|&c| c.id() == id)
+    /// Finds a character by its id, falling back to the legacy ids of the included characters.
+    /// Homebrew characters are stored with their legacy id, so they are found exactly.
+    pub fn get_character(&self, id: &str) -> Option<&Character> {
+        self.characters().find(|&c| c.has_id(id)).or_else(|| {
+            self.included_data
+                .characters
+                .iter()
+                .find(|c| c.has_legacy_id(id))
+        })
+    }
+
+    /// The id scripts use for the character that `id` refers to.
+    fn canonical_script_id(&self, id: &str) -> Option<String> {
+        self.characters()
+            .find(|&c| c.has_script_id(id))
+            .or_else(|| {
+                self.included_data
+                    .characters
+                    .iter()
+                    .find(|c| c.has_legacy_script_id(id))
+            })
+            .map(Character::id_no_numeric)
+    }
+
+    /// The characters a script id refers to, which are all copies of one character
+    /// that only differ by a number, e.g. the Village Idiots.
+    fn script_id_characters(&self, id: &str) -> Vec<&Character> {
+        let Some(id) = self.canonical_script_id(id) else {
+            return Vec::new();
+        };
+        self.characters().filter(|c| c.has_script_id(&id)).collect()
+    }
+
+    /// Gives homebrew characters without an id the id they were referred to by
+    /// before ids were explicit, so that it doesn't change with their name.
+    fn assign_homebrew_ids(&mut self) {
+        let mut taken: Vec<String> = self
+            .included_data
+            .characters
+            .iter()
+            .chain(
+                self.user_data
+                    .characters
+                    .iter()
+                    .filter(|c| !c.id.is_empty()),
+            )
+            .map(Character::id)
+            // included characters were found first by their legacy ids as well
+            .chain(
+                self.included_data
+                    .characters
+                    .iter()
+                    .map(Character::legacy_id),
+            )
+            .collect();
+        for c in &mut self.user_data.characters {
+            if c.id.is_empty() {
+                let mut id = c.legacy_id();
+                while taken.contains(&id) {
+                    id.push_str("_homebrew");
+                }
+                taken.push(id.clone());
+                c.id = id;
+            }
+        }
+    }
+
+    /// Rewrites legacy character ids in the user's scripts, tags and homebrew characters
+    /// to the current ids. Tag constraints only refer to tags, so they need no migration.
+    pub fn migrate_legacy_ids(&mut self) {
+        self.assign_homebrew_ids();
+
+        let mut scripts = std::mem::take(&mut self.user_data.scripts);
+        for script in &mut scripts {
+            let mut characters = Vec::new();
+            for id in &script.characters {
+                let id = self.canonical_script_id(id).unwrap_or(id.clone());
+                if !characters.contains(&id) {
+                    characters.push(id);
+                }
+            }
+            script.characters = characters;
+            for id in script
+                .first_night
+                .iter_mut()
+                .chain(script.other_night.iter_mut())
+                .flatten()
+            {
+                if let Some(canonical) = self.canonical_script_id(id) {
+                    *id = canonical;
+                }
+            }
+        }
+        self.user_data.scripts = scripts;
+
+        let tags = std::mem::take(&mut self.user_data.tags);
+        for (id, tags) in tags {
+            let id = self.get_character(&id).map(Character::id).unwrap_or(id);
+            self.user_data.tags.entry(id).or_default().extend(tags);
+        }
+
+        let canonical: HashMap<String, String> = self
+            .user_data
+            .characters
+            .iter()
+            .flat_map(|c| c.conditions.iter().flatten())
+            .flat_map(Condition::characters)
+            .filter_map(|id| Some((id.to_string(), self.get_character(id)?.id())))
+            .collect();
+        for id in self
+            .user_data
+            .characters
+            .iter_mut()
+            .flat_map(|c| c.conditions.iter_mut().flatten())
+            .flat_map(Condition::characters_mut)
+        {
+            if let Some(canonical) = canonical.get(id) {
+                *id = canonical.clone();
+            }
+        }
     }
 
     pub fn scripts(&self) -> impl Iterator<Item = Script> {
@@ -98,43 +383,477 @@ impl State {
                 it.characters = it
                     .characters
                     .iter()
-                    .flat_map(|id| {
-                        self.characters()
-                            .filter(|&c| (&c.id_no_numeric() == id))
-                            .map(|c| c.id())
-                            .collect::<Vec<_>>()
-                    })
+                    .flat_map(|id| self.script_id_characters(id))
+                    .map(Character::id)
                     .collect();
                 it
             })
     }
 
-    fn get_current_script(&self) -> Option<Script> {
+    pub fn get_current_script(&self) -> Option<Script> {
         self.scripts().find(|s| s.name == self.script)
     }
 
+    /// The current script as it is stored, without filtering its characters.
+    pub fn get_current_script_data(&self) -> Option<&Script> {
+        self.included_data
+            .scripts
+            .iter()
+            .chain(self.user_data.scripts.iter())
+            .find(|s| s.name == self.script)
+    }
+
     pub fn import_script(&mut self) {
-        let Ok(mut new_script) =
-            super::data::import_script(&self.script_input).inspect_err(|e| tracing::error!(?e))
+        let input = std::mem::take(&mut self.script_input);
+        self.add_pending_import("Pasted script".to_string(), &input);
+    }
+
+    pub fn add_pending_import(&mut self, source: String, json: &str) {
+        let result = super::data::import_script(json)
+            .inspect_err(|e| tracing::error!(?e))
+            .map(|mut import| {
+                let report = &mut import.report;
+                let mut characters = Vec::new();
+                for id in std::mem::take(&mut import.script.characters) {
+                    let canonical = match self.canonical_script_id(&id) {
+                        Some(canonical) => {
+                            if canonical != id {
+                                report.normalized.push((id, canonical.clone()));
+                            }
+                            canonical
+                        }
+                        None => {
+                            report.unknown.push(id.clone());
+                            id
+                        }
+                    };
+                    if characters.contains(&canonical) {
+                        if !report.duplicates.contains(&canonical) {
+                            report.duplicates.push(canonical);
+                        }
+                    } else {
+                        characters.push(canonical);
+                    }
+                }
+                import.script.characters = characters;
+                for id in import
+                    .script
+                    .first_night
+                    .iter_mut()
+                    .chain(import.script.other_night.iter_mut())
+                    .flatten()
+                {
+                    if let Some(canonical) = self.canonical_script_id(id) {
+                        *id = canonical;
+                    }
+                }
+                import
+            });
+        self.pending_imports.push(PendingImport { source, result });
+    }
+
+    pub fn confirm_import(&mut self, index: usize) {
+        if index >= self.pending_imports.len() {
+            return;
+        }
+        let Ok(ScriptImport {
+            script: mut new_script,
+            distribution,
+            ..
+        }) = self.pending_imports.remove(index).result
         else {
-            gloo_dialogs::alert("Invalid script format");
             return;
         };
 
-        // Ensure the script name is unique
-        let base_name = new_script.name.clone();
+        // Known tables are reused, unknown ones are added to the user's tables
+        if let Some(mut table) = distribution
+            && !self.distribution_tables().any(|t| t == &table)
+        {
+            let base_name = table.name.clone();
+            let mut i = 0;
+            while self.distribution_tables().any(|t| t.name == table.name) {
+                i += 1;
+                table.name = format!("{base_name} ({i})");
+            }
+            new_script.distribution = Some(table.name.clone());
+            self.user_data.distributions.push(table);
+        }
+        new_script.name = self.unique_script_name(&new_script.name);
+        self.user_data.scripts.push(new_script);
+    }
+
+    fn unique_script_name(&self, base_name: &str) -> String {
+        let mut name = base_name.to_string();
         let mut i = 0;
-        while self.scripts().any(|s| s.name == new_script.name) {
+        while self.scripts().any(|s| s.name == name) {
             i += 1;
-            new_script.name = format!("{base_name} ({i})");
+            name = format!("{base_name} ({i})");
         }
+        name
+    }
 
-        self.user_data.scripts.push(new_script);
+    pub fn new_script(&mut self) {
+        let script = Script {
+            name: "My Script".to_string(),
+            ..Default::default()
+        };
+        self.script_editor = Some(ScriptEditor::new(script, None));
+    }
+
+    pub fn clone_current_script(&mut self) {
+        let Some(mut script) = self.get_current_script_data().cloned() else {
+            return;
+        };
+        script.name = format!("{} (copy)", script.name);
+        self.script_editor = Some(ScriptEditor::new(script, None));
+    }
+
+    pub fn edit_current_script(&mut self) {
+        let Some(script) = self
+            .user_data
+            .scripts
+            .iter()
+            .find(|s| s.name == self.script)
+            .cloned()
+        else {
+            return;
+        };
+        self.script_editor = Some(ScriptEditor::new(script, Some(self.script.clone())));
+    }
+
+    pub fn save_script_editor(&mut self) {
+        let Some(ScriptEditor {
+            mut script,
+            editing,
+            ..
+        }) = self.script_editor.take()
+        else {
+            return;
+        };
+
+        let name = script.name.trim();
+        let name = if name.is_empty() { "My Script" } else { name };
+        // An edited script keeps its place in the list
+        let position = editing.as_ref().and_then(|old_name| {
+            self.user_data
+                .scripts
+                .iter()
+                .position(|s| &s.name == old_name)
+        });
+        if let Some(i) = position {
+            self.user_data.scripts.remove(i);
+        }
+        script.name = self.unique_script_name(name);
+
+        if editing.is_some() {
+            let on_script: Vec<String> = self
+                .selected
+                .keys()
+                .filter(|&id| {
+                    self.get_character(id)
+                        .is_some_and(|c| script.characters.iter().any(|id| c.has_script_id(id)))
+                })
+                .cloned()
+                .collect();
+            self.selected.retain(|id, _| on_script.contains(id));
+        } else {
+            self.selected.clear();
+        }
+        self.script = script.name.clone();
+        match position {
+            Some(i) => self.user_data.scripts.insert(i, script),
+            None => self.user_data.scripts.push(script),
+        }
+    }
+
+    pub fn distribution_tables(&self) -> impl Iterator<Item = &DistributionTable> {
+        self.included_data
+            .distributions
+            .iter()
+            .chain(self.user_data.distributions.iter())
+    }
+
+    /// The distribution table of the current script.
+    pub fn distribution_table(&self) -> DistributionTable {
+        let name = self
+            .get_current_script_data()
+            .and_then(|s| s.distribution.clone())
+            .unwrap_or(crate::consts::DEFAULT_DISTRIBUTION.to_string());
+        self.distribution_tables()
+            .find(|t| t.name == name)
+            .or_else(|| {
+                self.distribution_tables()
+                    .find(|t| t.name == crate::consts::DEFAULT_DISTRIBUTION)
+            })
+            .cloned()
+            .unwrap_or_else(|| {
+                tracing::error!("Distribution table not found: {name}");
+                DistributionTable {
+                    name,
+                    script_counts: [0; 4],
+                    rows: Default::default(),
+                }
+            })
+    }
+
+    pub fn set_script_distribution(&mut self, table: String) {
+        if let Some(script) = self
+            .user_data
+            .scripts
+            .iter_mut()
+            .find(|s| s.name == self.script)
+        {
+            script.distribution = (table != crate::consts::DEFAULT_DISTRIBUTION).then_some(table);
+        }
+        self.update_type_counts();
+    }
+
+    pub fn save_distribution_table(&mut self) {
+        let table =
+            match DistributionTable::parse(&self.distribution_name_input, &self.distribution_input)
+            {
+                Ok(table) => table,
+                Err(e) => {
+                    gloo_dialogs::alert(&e);
+                    return;
+                }
+            };
+        if self
+            .included_data
+            .distributions
+            .iter()
+            .any(|t| t.name == table.name)
+        {
+            gloo_dialogs::alert(
+                &self
+                    .user_data
+                    .language
+                    .tf("\"{name}\" is a built-in table", &[("name", &table.name)]),
+            );
+            return;
+        }
+        self.user_data
+            .distributions
+            .retain(|t| t.name != table.name);
+        self.user_data.distributions.push(table);
+        self.distribution_name_input.clear();
+        self.distribution_input.clear();
+        self.update_type_counts();
+    }
+
+    fn script_key(&self) -> ScriptKey {
+        ScriptKey {
+            characters: self.script_characters().iter().map(|c| c.id()).collect(),
+            table: self.distribution_table(),
+        }
+    }
+
+    /// The most travellers the current script can add to a game.
+    pub fn max_analysis_travellers(&self) -> u8 {
+        let travellers = self
+            .script_characters()
+            .iter()
+            .filter(|c| c.r#type == Type::Traveller)
+            .count();
+        travellers.min(crate::consts::MAX_TRAVELLERS.into()) as u8
+    }
+
+    /// Sets the travellers for the feasibility table, at most as many as the script has.
+    pub fn set_analysis_travellers(&mut self, count: u8) {
+        self.analysis_travellers = count.min(self.max_analysis_travellers());
+    }
+
+    /// Reruns the checks of the current script if it, its distribution table
+    /// or the amount of travellers changed since they last ran.
+    /// They are only shown in the script menu, so they don't run while it is closed.
+    pub fn refresh_script_checks(&mut self) {
+        if !self.expanded_script_menu {
+            return;
+        }
+        // the script may have lost travellers since they were set
+        self.set_analysis_travellers(self.analysis_travellers);
+        let key = self.script_key();
+        if self.script_lints.as_ref().is_none_or(|(k, _)| k != &key) {
+            let lints = lint_script(
+                &self.script_characters(),
+                &key.table,
+                &self.included_data.jinxes,
+            );
+            self.script_lints = Some((key.clone(), lints));
+        }
+        if self
+            .script_feasibility
+            .as_ref()
+            .is_none_or(|(k, travellers, _)| k != &key || *travellers != self.analysis_travellers)
+        {
+            let rows = feasibility(
+                &self.script_characters(),
+                &key.table,
+                self.analysis_travellers,
+            );
+            self.script_feasibility = Some((key, self.analysis_travellers, rows));
+        }
+    }
+
+    pub fn satisfies_tag_constraints(&self, characters: &[&Character]) -> bool {
+        self.user_data
+            .tag_constraints
+            .iter()
+            .all(|c| c.is_satisfied(characters, &self.user_data.tags))
+    }
+
+    pub fn add_user_tag(&mut self) {
+        let tag = self.tag_input.trim().to_string();
+        let language = self.user_data.language;
+        let name = self.tag_character_input.trim();
+        let Some(id) = self
+            .characters()
+            .find(|&c| {
+                c.name.eq_ignore_ascii_case(name) || language.name(c).eq_ignore_ascii_case(name)
+            })
+            .map(Character::id)
+        else {
+            gloo_dialogs::alert(&language.tf(
+                "Unknown character: {name}",
+                &[("name", &self.tag_character_input)],
+            ));
+            return;
+        };
+        if tag.is_empty() {
+            return;
+        }
+        let tags = self.user_data.tags.entry(id).or_default();
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+        self.tag_input.clear();
+    }
+
+    pub fn remove_user_tag(&mut self, id: &str, tag: &str) {
+        if let Some(tags) = self.user_data.tags.get_mut(id) {
+            tags.retain(|t| t != tag);
+            if tags.is_empty() {
+                self.user_data.tags.remove(id);
+            }
+        }
+    }
+
+    pub fn balance(&self) -> Balance {
+        Balance::of(&self.selected_characters())
     }
 
     pub fn is_valid_character_list(&self) -> bool {
-        let (valid, extra) = validate_list(&self.selected_characters(), self.type_counts());
-        valid && self.selected.len() == (self.player_count + extra) as usize
+        let characters = self.selected_characters();
+        validate_list(&characters, self.player_count, self.type_counts())
+            && self.satisfies_tag_constraints(&characters)
+    }
+
+    /// The value chosen for every variable of the selected characters,
+    /// along with the character that defines it.
+    pub fn variables(&self) -> Vec<(&Character, &str, usize)> {
+        let characters = self.selected_characters();
+        let mut variables = Vec::new();
+        for &c in &characters {
+            for condition in c.conditions.iter().flatten() {
+                if let Condition::Variable {
+                    variable, r#type, ..
+                } = condition
+                {
+                    let value = characters.iter().filter(|c| c.r#type == *r#type).count();
+                    variables.push((c, variable.as_str(), value));
+                }
+            }
+        }
+        variables
+    }
+
+    pub fn sheet_characters(&self, scope: SheetScope) -> Vec<&Character> {
+        match scope {
+            SheetScope::Script => self.script_characters(),
+            SheetScope::Selected => self.selected_characters(),
+        }
+    }
+
+    pub fn setup_notes(&self) -> Vec<SetupNote> {
+        setup_notes(
+            &self.selected_characters(),
+            &self.script_characters(),
+            self.player_count,
+            self.user_data.language,
+        )
+    }
+
+    /// The characters in their seats, or `None` if the seats don't match the selected characters.
+    pub fn seats(&self) -> Option<Vec<&Character>> {
+        let seats = self
+            .seats
+            .iter()
+            .map(|id| self.get_character(id))
+            .collect::<Option<Vec<_>>>()?;
+        let mut expected: Vec<String> =
+            seating::seated(&self.selected_characters(), self.player_count)
+                .into_iter()
+                .map(Character::id)
+                .collect();
+        let mut actual = self.seats.clone();
+        expected.sort_unstable();
+        actual.sort_unstable();
+        (expected == actual).then_some(seats)
+    }
+
+    pub fn deal_seats(&mut self) {
+        self.selected_seat = None;
+        let mut rng = rand::rng();
+        let characters = seating::seated(&self.selected_characters(), self.player_count);
+        match seating::deal(&characters, &mut rng) {
+            Some((i, seats)) => {
+                tracing::info!("Valid seating found after {i} iterations");
+                self.seats = seats.into_iter().map(Character::id).collect();
+                self.bluffs = self
+                    .script_characters()
+                    .into_iter()
+                    .filter(|c| c.r#type.is_good() && !self.selected.contains_key(&c.id()))
+                    .choose_multiple(&mut rng, 3)
+                    .into_iter()
+                    .map(Character::id)
+                    .collect();
+                self.reveal_seed = rng.random();
+            }
+            None => {
+                let iterations = crate::consts::MAX_GENERATION_ITERATIONS;
+                tracing::error!("Failed to deal seats after {iterations} iterations");
+                gloo_dialogs::alert(&self.user_data.language.tf(
+                    "Failed to deal seats after {iterations} iterations",
+                    &[("iterations", &iterations)],
+                ));
+            }
+        }
+    }
+
+    /// Reveal links per seat, or `None` if no seats have been dealt.
+    pub fn reveal_links(&self, base_url: &str) -> Option<Vec<String>> {
+        let seats = self.seats()?;
+        let links = reveal::reveals(
+            &seats,
+            &self.selected_characters(),
+            &self.script_characters(),
+            &self.bluffs,
+            self.player_count,
+            &mut StdRng::seed_from_u64(self.reveal_seed),
+        )
+        .iter()
+        .map(|r| format!("{base_url}#reveal={}", reveal::encode(r)))
+        .collect();
+        Some(links)
+    }
+
+    /// Selects a seat, or swaps it with the selected one.
+    pub fn click_seat(&mut self, seat: usize) {
+        match self.selected_seat.take() {
+            Some(other) if other != seat => self.seats.swap(other, seat),
+            Some(_) => {}
+            None => self.selected_seat = Some(seat),
+        }
     }
 
     pub fn randomize_unlocked(&mut self) {
@@ -146,8 +865,8 @@ impl State {
 
         let locked: Vec<_> = self
             .selected
-            .iter()
-            .filter_map(|(id, _)| self.script_characters().into_iter().find(|c| &c.id() == id))
+            .keys()
+            .filter_map(|id| self.script_characters().into_iter().find(|c| &c.id() == id))
             .collect();
 
         match self.get_randomized_characters(&locked, &old_unlocked) {
@@ -157,84 +876,232 @@ impl State {
                     .extend(new_selected.into_iter().map(|id| (id, Default::default())));
             }
             None => {
-                let err = format!(
-                    "Failed to randomize after {} iterations",
-                    crate::consts::MAX_GENERATION_ITERATIONS
-                );
-                tracing::error!(err);
-                gloo_dialogs::alert(&err);
+                let iterations = crate::consts::MAX_GENERATION_ITERATIONS;
+                tracing::error!("Failed to randomize after {iterations} iterations");
+                gloo_dialogs::alert(&self.user_data.language.tf(
+                    "Failed to randomize after {iterations} iterations",
+                    &[("iterations", &iterations)],
+                ));
             }
         }
     }
 
-    fn get_randomized_characters(
+    /// Picks a list uniformly at random from all valid lists that contain the locked characters,
+    /// are different from the old list, are within the balance target
+    /// and satisfy the tag constraints.
+    /// Fabled and Travellers are never picked.
+    pub fn get_randomized_characters(
         &self,
         locked: &[&Character],
         old_unlocked: &BTreeSet<String>,
     ) -> Option<(usize, BTreeSet<String>)> {
-        let missing = self.player_count as usize - locked.len();
-
-        let character_pool: Vec<&Character> = self
-            .script_characters()
-            .into_iter()
-            .filter(|c| !locked.iter().any(|locked_c| locked_c.id() == c.id()))
-            .collect();
+        let script_characters = self.script_characters();
+        let pool = Pool::new(&script_characters);
+        let setups = self.setups(&script_characters, &pool, locked);
+        let new_unlocked = |list: &[&Character]| -> BTreeSet<String> {
+            list.iter()
+                .map(|c| c.id())
+                .filter(|id| !locked.iter().any(|l| &l.id() == id))
+                .collect()
+        };
+        let (i, list) = self.generate(&pool, &setups, locked, &mut rand::rng(), |list| {
+            &new_unlocked(list) != old_unlocked
+        })?;
+        Some((i, new_unlocked(&list)))
+    }
 
-        let mut extras_set = HashSet::from([0]);
+    /// Samples lists from `setups` until one is within the balance target,
+    /// satisfies the tag constraints and is accepted by `accept`.
+    /// Returns the list and the amount of iterations it took.
+    pub fn generate<'a, R: Rng + ?Sized>(
+        &self,
+        pool: &Pool<'a>,
+        setups: &Option<Vec<Setup>>,
+        locked: &[&Character],
+        rng: &mut R,
+        accept: impl Fn(&[&Character]) -> bool,
+    ) -> Option<(usize, Vec<&'a Character>)> {
+        let type_counts = self.type_counts();
         for i in 0..crate::consts::MAX_GENERATION_ITERATIONS {
-            for current_extras in extras_set.clone() {
-                let new = character_pool
-                    .choose_multiple(&mut rand::rng(), missing + current_extras as usize)
-                    .copied()
-                    .collect::<Vec<_>>();
-
-                let (valid, extras) = validate_list(&[locked, &new].concat(), self.type_counts());
-                extras_set.insert(extras);
-
-                if valid && extras == current_extras {
-                    let new_unlocked = new.into_iter().map(Character::id).collect::<BTreeSet<_>>();
-                    if &new_unlocked != old_unlocked {
-                        return Some((i + 1, new_unlocked));
-                    }
-                }
+            let list = match setups {
+                Some(setups) => analysis::sample(pool, setups, locked, rng)?,
+                None => match analysis::sample_rejection(
+                    pool,
+                    locked,
+                    self.player_count,
+                    &type_counts,
+                    rng,
+                ) {
+                    Some(list) => list,
+                    None => continue,
+                },
+            };
+            if let Some(target) = &self.balance_target
+                && !target.contains(&Balance::of(&list).score())
+            {
+                continue;
+            }
+            if !self.satisfies_tag_constraints(&list) {
+                continue;
+            }
+            if accept(&list) {
+                return Some((i + 1, list));
             }
         }
 
         None
     }
 
+    /// The setups of the script for the player count and locked characters,
+    /// `None` if there are too many to enumerate them.
+    /// Cached, since enumerating them takes a while for large scripts.
+    pub fn setups(
+        &self,
+        script_characters: &[&Character],
+        pool: &Pool,
+        locked: &[&Character],
+    ) -> CachedSetups {
+        let type_counts = self.type_counts();
+        let key = SetupsKey {
+            characters: script_characters.iter().map(|c| c.id()).collect(),
+            locked: locked.iter().map(|c| c.id()).collect(),
+            player_count: self.player_count,
+            type_counts: type_counts.clone().into_iter().collect(),
+        };
+        if let Some((cached_key, setups)) = &*self.setups_cache.borrow()
+            && cached_key == &key
+        {
+            return setups.clone();
+        }
+        let setups = Rc::new(analysis::setups(
+            pool,
+            locked,
+            self.player_count,
+            &type_counts,
+        ));
+        *self.setups_cache.borrow_mut() = Some((key, setups.clone()));
+        setups
+    }
+
+    /// Every allowed count per type, before applying any conditions.
     fn type_counts(&self) -> HashMap<Type, BTreeSet<i8>> {
+        let range = |count: &RangeInclusive<u8>| count.clone().map(|c| c as i8).collect();
         let mut type_counts = HashMap::new();
-        type_counts.insert(Type::Outsider, BTreeSet::from([self.outsider_count as i8]));
-        type_counts.insert(Type::Minion, BTreeSet::from([self.minion_count as i8]));
-        type_counts.insert(Type::Demon, BTreeSet::from([self.demon_count as i8]));
+        type_counts.insert(Type::Outsider, range(&self.outsider_count));
+        type_counts.insert(Type::Minion, range(&self.minion_count));
+        type_counts.insert(Type::Demon, range(&self.demon_count));
         type_counts
     }
 }
 
-fn validate_list(
+pub fn exact_type_counts(outsiders: u8, minions: u8, demons: u8) -> HashMap<Type, BTreeSet<i8>> {
+    let mut type_counts = HashMap::new();
+    type_counts.insert(Type::Outsider, BTreeSet::from([outsiders as i8]));
+    type_counts.insert(Type::Minion, BTreeSet::from([minions as i8]));
+    type_counts.insert(Type::Demon, BTreeSet::from([demons as i8]));
+    type_counts
+}
+
+/// Whether the conditions of the characters can be satisfied
+/// by a list of exactly this size for the player count.
+pub fn validate_list(
     characters: &[&Character],
-    mut type_counts: HashMap<Type, BTreeSet<i8>>,
-) -> (bool, u8) {
-    let conditions: Vec<_> = characters
+    player_count: u8,
+    type_counts: HashMap<Type, BTreeSet<i8>>,
+) -> bool {
+    list_alternatives(characters, player_count)
         .iter()
-        .filter_map(|c| c.conditions.clone())
-        .flatten()
-        .collect();
+        .any(|conditions| {
+            let (valid, size) =
+                validate_alternative(characters, conditions, player_count, type_counts.clone());
+            valid && characters.len() == size
+        })
+}
 
+/// Every list size the conditions of the characters allow for the player count.
+pub fn list_sizes(characters: &[&Character], player_count: u8) -> BTreeSet<usize> {
+    list_alternatives(characters, player_count)
+        .iter()
+        .map(|conditions| {
+            validate_alternative(characters, conditions, player_count, HashMap::new()).1
+        })
+        .collect()
+}
+
+/// Types whose characters are in play but not dealt to players.
+pub fn undealt_types(characters: &[&Character]) -> BTreeSet<Type> {
+    characters
+        .iter()
+        .flat_map(|c| c.conditions.iter().flatten())
+        .filter_map(|cond| match cond {
+            Condition::Replace { replace, .. } => Some(*replace),
+            Condition::ChosenAfterSetup { chosen_after_setup } => Some(*chosen_after_setup),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Types whose characters are chosen by a character after setup instead of being dealt.
+fn chosen_types(characters: &[&Character]) -> BTreeSet<Type> {
+    characters
+        .iter()
+        .flat_map(|c| c.conditions.iter().flatten())
+        .filter_map(|cond| match cond {
+            Condition::ChosenAfterSetup { chosen_after_setup } => Some(*chosen_after_setup),
+            _ => None,
+        })
+        .collect()
+}
+
+fn list_alternatives(characters: &[&Character], player_count: u8) -> Vec<Vec<Condition>> {
+    // Characters chosen after setup don't modify the setup
+    let chosen = chosen_types(characters);
+    condition::alternatives(
+        characters
+            .iter()
+            .filter(|c| !chosen.contains(&c.r#type))
+            .flat_map(|c| c.conditions.iter().flatten()),
+        player_count,
+    )
+}
+
+/// Validates the list against one alternative of its conditions.
+/// Returns whether the type counts fit and the size the list needs to have.
+fn validate_alternative(
+    characters: &[&Character],
+    conditions: &[Condition],
+    player_count: u8,
+    mut type_counts: HashMap<Type, BTreeSet<i8>>,
+) -> (bool, usize) {
     let mut type_is_any_count: HashMap<Type, bool> = HashMap::new();
     let mut saturating_subs: HashMap<Type, BTreeSet<u8>> = HashMap::new();
 
     let mut valid = true;
     let mut extras = 0;
     let mut extras_by_type = HashMap::new();
-    for condition in conditions {
+    let mut undealt = BTreeSet::new();
+    let mut chosen = BTreeSet::new();
+    let mut shared_seats = 0;
+    let mut variables: HashMap<Type, RangeInclusive<u8>> = HashMap::new();
+    let type_count = |r#type| characters.iter().filter(|c| c.r#type == r#type).count() as u8;
+    for condition in conditions.iter().cloned() {
         match condition {
             Condition::Character { character } => {
-                if !characters.iter().any(|c| c.id() == character) {
+                if !characters.iter().any(|c| c.has_id(&character)) {
                     valid = false;
                 }
             }
+            Condition::Excludes { character } => {
+                if characters.iter().any(|c| c.has_id(&character)) {
+                    valid = false;
+                }
+            }
+            Condition::AnyOf { .. }
+            | Condition::AllOf { .. }
+            | Condition::Not { .. }
+            | Condition::WhenPlayers { .. } => {
+                unreachable!("alternatives contain no combinators")
+            }
             Condition::Type {
                 r#type,
                 amount: TypeCond::None,
@@ -275,15 +1142,43 @@ fn validate_list(
             Condition::ExtraCharacters {
                 extra_characters: ExtraCharacters::Type(r#type, sub),
             } => {
-                let type_count = characters.iter().filter(|c| c.r#type == r#type).count() as u8;
-                let extra = type_count.saturating_sub(sub);
+                let extra = type_count(r#type).saturating_sub(sub);
                 extras_by_type
                     .entry(r#type)
                     .and_modify(|e: &mut u8| *e = (*e).max(extra))
                     .or_insert(extra);
             }
+            Condition::Replace {
+                replace,
+                with,
+                amount,
+            } => {
+                undealt.insert(replace);
+                // Townsfolk fill the remaining seats anyway
+                if let Some(counts) = type_counts.get_mut(&with) {
+                    let added = (type_count(replace) * amount) as i8;
+                    *counts = counts.iter().map(|count| count + added).collect();
+                }
+            }
+            Condition::ChosenAfterSetup { chosen_after_setup } => {
+                undealt.insert(chosen_after_setup);
+                chosen.insert(chosen_after_setup);
+            }
+            Condition::Seats { seats } => {
+                shared_seats += seats.count(player_count).saturating_sub(1) as usize;
+            }
+            Condition::Variable {
+                r#type, min, max, ..
+            } => {
+                let range = match variables.get(&r#type) {
+                    Some(range) => *range.start().max(&min)..=*range.end().min(&max),
+                    None => min..=max,
+                };
+                variables.insert(r#type, range);
+            }
         }
     }
+    let undealt: u8 = undealt.into_iter().map(type_count).sum();
 
     for (r#type, amounts) in saturating_subs {
         let counts = type_counts.entry(r#type).or_default();
@@ -299,17 +1194,26 @@ fn validate_list(
         *counts = new_counts;
     }
 
+    for (r#type, range) in variables {
+        type_counts.insert(r#type, range.map(|c| c as i8).collect());
+        type_is_any_count.remove(&r#type);
+    }
+
     for (r#type, counts) in type_counts {
         if *type_is_any_count.get(&r#type).unwrap_or(&false) {
             continue;
         }
         let actual_count = characters.iter().filter(|c| c.r#type == r#type).count() as i8;
-        if !counts.contains(&actual_count) {
+        // The characters chosen after setup can be left to the storyteller
+        let left_to_choose = chosen.contains(&r#type) && actual_count == 0;
+        if !counts.contains(&actual_count) && !left_to_choose {
             valid = false;
         }
     }
 
-    (valid, extras + extras_by_type.values().sum::<u8>())
+    let extras = extras + extras_by_type.values().sum::<u8>() + undealt;
+    let size = (player_count as usize + extras as usize).saturating_sub(shared_seats);
+    (valid, size)
 }
 
 pub fn group_characters_by_type<'a>(
@@ -327,3 +1231,393 @@ pub fn group_characters_by_type<'a>(
     }
     grouped
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logic::{character::Category, tags::TagConstraint};
+
+    /// A state with a homebrew script of the characters for 5 players.
+    fn script_state(characters: Vec<Character>) -> State {
+        let user_data = UserData {
+            scripts: vec![Script {
+                name: "Test".to_string(),
+                characters: characters.iter().map(Character::id).collect(),
+                ..Default::default()
+            }],
+            characters,
+            ..Default::default()
+        };
+        let included_data = IncludedData {
+            characters: Vec::new(),
+            scripts: Vec::new(),
+            jinxes: Vec::new(),
+            distributions: vec![
+                DistributionTable::parse(crate::consts::DEFAULT_DISTRIBUTION, "5: 0/1/1").unwrap(),
+            ],
+        };
+        let mut state = State::new(included_data, user_data);
+        state.script = "Test".to_string();
+        state.player_count = 5;
+        state.update_type_counts();
+        state
+    }
+
+    #[test]
+    fn characters_chosen_after_setup_dont_modify_the_setup() {
+        let summoner = Character::test("Summoner", Type::Minion)
+            .with_conditions(r#"[{"chosen_after_setup": "DEMON"}]"#);
+        let fang_gu = Character::test("Fang Gu", Type::Demon)
+            .with_conditions(r#"[{"type": "OUTSIDER", "amount": {"ADD": [1]}}]"#);
+        let townsfolk: Vec<Character> = (1..=6)
+            .map(|i| Character::test(&format!("Townsfolk {i}"), Type::Townsfolk))
+            .collect();
+        let outsider = Character::test("Outsider", Type::Outsider);
+        let minion = Character::test("Minion", Type::Minion);
+        fn list<'a>(townsfolk: &'a [Character], others: &[&'a Character]) -> Vec<&'a Character> {
+            townsfolk.iter().chain(others.iter().copied()).collect()
+        }
+        let valid = |list: &[&Character]| validate_list(list, 7, exact_type_counts(0, 1, 1));
+
+        // The Fang Gu adds an Outsider when it is dealt
+        assert!(valid(&list(
+            &townsfolk[..4],
+            &[&outsider, &minion, &fang_gu]
+        )));
+        assert!(!valid(&list(&townsfolk[..5], &[&minion, &fang_gu])));
+        // The Demon the Summoner picks isn't dealt, so it takes no seat and adds no Outsider
+        assert!(valid(&list(&townsfolk[..6], &[&summoner, &fang_gu])));
+        assert!(!valid(&list(
+            &townsfolk[..5],
+            &[&outsider, &summoner, &fang_gu]
+        )));
+        assert!(!valid(&list(&townsfolk[..5], &[&summoner, &fang_gu])));
+        // The Demon can be left for the Summoner to pick
+        assert!(valid(&list(&townsfolk[..6], &[&summoner])));
+        assert!(!valid(&list(&townsfolk[..5], &[&summoner])));
+        assert_eq!(
+            list_sizes(&list(&townsfolk[..6], &[&summoner]), 7),
+            [7].into()
+        );
+        assert_eq!(
+            list_sizes(&list(&townsfolk[..6], &[&summoner, &fang_gu]), 7),
+            [8].into()
+        );
+    }
+
+    #[test]
+    fn migrate_legacy_ids() {
+        let included_data = IncludedData {
+            characters: vec![
+                Character::test("Fortune Teller", Type::Townsfolk),
+                Character::test("Lil' Monsta", Type::Demon),
+            ],
+            scripts: Vec::new(),
+            jinxes: Vec::new(),
+            distributions: Vec::new(),
+        };
+        let homebrew = |name: &str, conditions| Character {
+            conditions,
+            ..Character::test(name, Type::Outsider)
+        };
+        let user_data = UserData {
+            characters: vec![
+                homebrew(
+                    "Foo Bar",
+                    Some(vec![Condition::Not {
+                        not: Box::new(Condition::Character {
+                            character: "fortuneteller".to_string(),
+                        }),
+                    }]),
+                ),
+                homebrew("Lil Monsta", None),
+            ],
+            scripts: vec![Script {
+                name: "Old".to_string(),
+                characters: ["fortuneteller", "fortune-teller", "lilmonsta", "foobar"]
+                    .map(String::from)
+                    .to_vec(),
+                first_night: Some(vec!["fortuneteller".to_string()]),
+                ..Default::default()
+            }],
+            tags: [("fortuneteller".to_string(), vec!["info".to_string()])].into(),
+            ..Default::default()
+        };
+        let mut state = State::new(included_data, user_data);
+        state.migrate_legacy_ids();
+
+        let ids: Vec<_> = state.user_data.characters.iter().map(|c| &c.id).collect();
+        assert_eq!(ids, ["foobar", "lilmonsta_homebrew"]);
+        assert_eq!(
+            state.user_data.characters[0].conditions.as_ref().unwrap()[0].characters(),
+            ["fortune_teller"]
+        );
+        let script = &state.user_data.scripts[0];
+        assert_eq!(
+            script.characters,
+            ["fortune_teller", "lil_monsta", "foobar"]
+        );
+        assert_eq!(script.first_night, Some(vec!["fortune_teller".to_string()]));
+        assert_eq!(
+            state.user_data.tags.keys().collect::<Vec<_>>(),
+            ["fortune_teller"]
+        );
+
+        // the official id never resolves to the homebrew character with the same legacy id
+        assert_eq!(
+            state
+                .get_character("lil_monsta")
+                .map(Character::id)
+                .as_deref(),
+            Some("lil_monsta")
+        );
+        assert_eq!(
+            state
+                .get_character("lilmonsta")
+                .map(Character::id)
+                .as_deref(),
+            Some("lil_monsta")
+        );
+        assert_eq!(
+            state
+                .scripts()
+                .find(|s| s.name == "Old")
+                .unwrap()
+                .characters,
+            ["fortune_teller", "lil_monsta", "foobar"]
+        );
+
+        // migrating again doesn't change anything
+        let user_data = state.user_data.clone();
+        state.migrate_legacy_ids();
+        assert_eq!(state.user_data, user_data);
+    }
+
+    #[test]
+    fn analysis_travellers_are_limited_to_the_script() {
+        let mut characters: Vec<Character> = [
+            "Washerwoman",
+            "Librarian",
+            "Investigator",
+            "Chef",
+            "Empath",
+            "Fortune Teller",
+        ]
+        .into_iter()
+        .map(|name| Character::test(name, Type::Townsfolk))
+        .collect();
+        characters.push(Character::test("Imp", Type::Demon));
+        characters.push(Character::test("Poisoner", Type::Minion));
+        characters.push(Character::test("Thief", Type::Traveller));
+        characters.push(Character::test("Scapegoat", Type::Traveller));
+        let mut state = script_state(characters);
+        state.expanded_script_menu = true;
+
+        assert_eq!(state.max_analysis_travellers(), 2);
+        state.set_analysis_travellers(5);
+        assert_eq!(state.analysis_travellers, 2);
+        state.refresh_script_checks();
+        let (_, travellers, rows) = state.script_feasibility.as_ref().unwrap();
+        assert_eq!(*travellers, 2);
+        let five_players = rows.iter().find(|row| row.player_count == 5).unwrap();
+        // 3 of the 6 Townsfolk, both travellers
+        assert_eq!(five_players.total_lists(), Some(20));
+
+        // removing a traveller from the script lowers the amount again
+        state.user_data.scripts[0].characters.pop();
+        state.refresh_script_checks();
+        assert_eq!(state.analysis_travellers, 1);
+    }
+
+    #[test]
+    fn generated_lists_are_within_the_balance_target() {
+        let with_categories = |name, r#type, categories: &[Category]| Character {
+            categories: categories.to_vec(),
+            ..Character::test(name, r#type)
+        };
+        let characters = vec![
+            with_categories("Chef", Type::Townsfolk, &[Category::Information]),
+            with_categories("Empath", Type::Townsfolk, &[Category::Information]),
+            with_categories("Monk", Type::Townsfolk, &[Category::Protection]),
+            with_categories("Soldier", Type::Townsfolk, &[]),
+            with_categories("Mayor", Type::Townsfolk, &[]),
+            with_categories("Poisoner", Type::Minion, &[Category::Droisoning]),
+            with_categories("Baron", Type::Minion, &[]),
+            with_categories("Imp", Type::Demon, &[Category::ExtraDeath]),
+        ];
+        let mut state = script_state(characters);
+        let script: Vec<Character> = state.script_characters().into_iter().cloned().collect();
+        let script_characters: Vec<&Character> = script.iter().collect();
+        let pool = Pool::new(&script_characters);
+        let setups = state.setups(&script_characters, &pool, &[]);
+        let mut rng = StdRng::seed_from_u64(0);
+
+        // at most 3 good points against 1 or 2 evil points
+        state.balance_target = Some(2..=2);
+        for _ in 0..20 {
+            let (_, list) = state
+                .generate(&pool, &setups, &[], &mut rng, |_| true)
+                .unwrap();
+            assert_eq!(Balance::of(&list).score(), 2);
+        }
+        state.balance_target = Some(3..=10);
+        assert_eq!(
+            state.generate(&pool, &setups, &[], &mut rng, |_| true),
+            None
+        );
+    }
+
+    #[test]
+    fn lists_satisfy_the_tag_constraints() {
+        let mut characters: Vec<Character> = ["Chef", "Empath", "Monk", "Soldier", "Mayor"]
+            .into_iter()
+            .map(|name| Character::test(name, Type::Townsfolk))
+            .collect();
+        characters.extend([
+            Character::test("Poisoner", Type::Minion),
+            Character::test("Imp", Type::Demon),
+        ]);
+        let mut state = script_state(characters);
+        state.user_data.tags = [
+            ("chef".to_string(), vec!["info".to_string()]),
+            ("empath".to_string(), vec!["info".to_string()]),
+        ]
+        .into();
+        state.user_data.tag_constraints = vec![TagConstraint {
+            tag: "info".to_string(),
+            r#type: Some(Type::Townsfolk),
+            min: Some(1),
+            max: Some(1),
+        }];
+
+        for (townsfolk, valid) in [
+            (["chef", "monk", "soldier"], true),
+            (["chef", "empath", "monk"], false),
+            (["monk", "soldier", "mayor"], false),
+        ] {
+            state.selected = townsfolk
+                .into_iter()
+                .chain(["poisoner", "imp"])
+                .map(|id| (id.to_string(), Selected::default()))
+                .collect();
+            assert_eq!(state.is_valid_character_list(), valid, "{townsfolk:?}");
+        }
+
+        let script: Vec<Character> = state.script_characters().into_iter().cloned().collect();
+        let script_characters: Vec<&Character> = script.iter().collect();
+        let pool = Pool::new(&script_characters);
+        let setups = state.setups(&script_characters, &pool, &[]);
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..20 {
+            let (_, list) = state
+                .generate(&pool, &setups, &[], &mut rng, |_| true)
+                .unwrap();
+            let info = list
+                .iter()
+                .filter(|c| ["chef", "empath"].contains(&c.id().as_str()))
+                .count();
+            assert_eq!(info, 1);
+        }
+    }
+
+    #[test]
+    fn type_count_ranges_stay_ordered_and_bounded() {
+        let mut state = script_state(Vec::new());
+        state.type_counts_locked = false;
+
+        state.set_type_count_min(Type::Outsider, 2);
+        assert_eq!(state.outsider_count, 2..=2);
+        state.set_type_count_max(Type::Outsider, 4);
+        assert_eq!(state.outsider_count, 2..=4);
+        // moving one end past the other moves both
+        state.set_type_count_min(Type::Outsider, 5);
+        assert_eq!(state.outsider_count, 5..=5);
+        state.set_type_count_max(Type::Outsider, 1);
+        assert_eq!(state.outsider_count, 1..=1);
+        // counts above the largest one are clamped
+        state.set_type_count_max(Type::Minion, 50);
+        assert_eq!(state.minion_count, 1..=crate::consts::MAX_TYPE_COUNT);
+        state.set_type_count_min(Type::Demon, 50);
+        assert_eq!(
+            state.demon_count,
+            crate::consts::MAX_TYPE_COUNT..=crate::consts::MAX_TYPE_COUNT
+        );
+    }
+
+    #[test]
+    fn variable_outsider_count_matches_x() {
+        let xaan = Character::test("Xaan", Type::Minion)
+            .with_conditions(r#"[{"variable": "X", "type": "OUTSIDER", "min": 1, "max": 4}]"#);
+        let townsfolk: Vec<Character> = (1..=7)
+            .map(|i| Character::test(&format!("Townsfolk {i}"), Type::Townsfolk))
+            .collect();
+        let outsiders: Vec<Character> = (1..=5)
+            .map(|i| Character::test(&format!("Outsider {i}"), Type::Outsider))
+            .collect();
+        let poisoner = Character::test("Poisoner", Type::Minion);
+        let imp = Character::test("Imp", Type::Demon);
+        // 10 players usually have no Outsiders, Xaan sets their amount to X instead
+        let valid = |x: usize| {
+            let list: Vec<&Character> = [&xaan, &poisoner, &imp]
+                .into_iter()
+                .chain(&outsiders[..x])
+                .chain(&townsfolk[..7 - x])
+                .collect();
+            validate_list(&list, 10, exact_type_counts(0, 2, 1))
+        };
+        assert!(!valid(0));
+        for x in 1..=4 {
+            assert!(valid(x), "X = {x}");
+        }
+        assert!(!valid(5));
+    }
+
+    #[test]
+    fn variables_are_consistent_across_the_list() {
+        let variable = |name, min, max| {
+            Character::test(name, Type::Minion).with_conditions(&format!(
+                r#"[{{"variable": "X", "type": "OUTSIDER", "min": {min}, "max": {max}}}]"#
+            ))
+        };
+        let mut characters = vec![
+            variable("Xaan", 1, 4),
+            // a homebrew Minion sharing the variable narrows it down
+            variable("Homebrew", 2, 5),
+            Character::test("Imp", Type::Demon),
+        ];
+        characters.extend(
+            ["Chef", "Empath", "Monk", "Soldier"]
+                .map(|name| Character::test(name, Type::Townsfolk)),
+        );
+        characters.extend(
+            ["Butler", "Saint", "Recluse"].map(|name| Character::test(name, Type::Outsider)),
+        );
+        let mut state = script_state(characters);
+        state.type_counts_locked = false;
+        state.minion_count = 2..=2;
+
+        for (outsiders, valid) in [(&["butler"][..], false), (&["butler", "saint"][..], true)] {
+            let townsfolk = ["chef", "empath", "monk", "soldier"];
+            state.selected = ["xaan", "homebrew", "imp"]
+                .iter()
+                .chain(outsiders)
+                .chain(&townsfolk[..6 - 3 - outsiders.len()])
+                .map(|id| (id.to_string(), Selected::default()))
+                .collect();
+            state.player_count = 6;
+            assert_eq!(state.is_valid_character_list(), valid, "{outsiders:?}");
+        }
+        let values: Vec<(String, &str, usize)> = state
+            .variables()
+            .into_iter()
+            .map(|(c, variable, value)| (c.id(), variable, value))
+            .collect();
+        assert_eq!(
+            values,
+            [
+                ("homebrew".to_string(), "X", 2),
+                ("xaan".to_string(), "X", 2)
+            ]
+        );
+    }
+}
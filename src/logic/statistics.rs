@@ -0,0 +1,78 @@
+use std::collections::BTreeMap;
+
+use super::{
+    analysis::{Distribution, PLAYER_TYPES, Pool},
+    character::{Character, Type},
+    state::State,
+};
+
+/// How often characters and type counts appear in randomly generated lists.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Statistics {
+    /// runs done so far
+    pub runs: usize,
+    pub total_runs: usize,
+    /// runs in which no valid list was found
+    pub failures: usize,
+    /// amount of lists each character of the script appeared in
+    pub characters: BTreeMap<String, usize>,
+    pub distributions: BTreeMap<Distribution, usize>,
+}
+
+impl Statistics {
+    pub fn new(script_characters: &[&Character], total_runs: usize) -> Self {
+        Self {
+            runs: 0,
+            total_runs,
+            failures: 0,
+            characters: script_characters
+                .iter()
+                .filter(|c| PLAYER_TYPES.contains(&c.r#type))
+                .map(|c| (c.id(), 0))
+                .collect(),
+            distributions: BTreeMap::new(),
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.runs >= self.total_runs
+    }
+
+    pub fn successes(&self) -> usize {
+        self.runs - self.failures
+    }
+
+    /// How often each amount of characters of a type appeared.
+    pub fn type_histogram(&self, r#type: Type) -> BTreeMap<u8, usize> {
+        let mut histogram = BTreeMap::new();
+        for (distribution, &n) in &self.distributions {
+            *histogram.entry(distribution.get(r#type)).or_default() += n;
+        }
+        histogram
+    }
+}
+
+/// Runs the generator `runs` more times for the current script and player count
+/// without any locked characters.
+/// The setups are only enumerated once, so this can be called in chunks.
+pub fn simulate(state: &State, statistics: &mut Statistics, runs: usize) {
+    let script_characters = state.script_characters();
+    let pool = Pool::new(&script_characters);
+    let setups = state.setups(&script_characters, &pool, &[]);
+    let mut rng = rand::rng();
+
+    for _ in 0..runs.min(statistics.total_runs - statistics.runs) {
+        statistics.runs += 1;
+        let Some((_, list)) = state.generate(&pool, &setups, &[], &mut rng, |_| true) else {
+            statistics.failures += 1;
+            continue;
+        };
+        for c in &list {
+            *statistics.characters.entry(c.id()).or_default() += 1;
+        }
+        *statistics
+            .distributions
+            .entry(Distribution::of(&list))
+            .or_default() += 1;
+    }
+}
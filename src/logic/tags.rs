@@ -0,0 +1,112 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::character::{Character, Type};
+
+/// Tags added by the user, by character id.
+pub type UserTags = BTreeMap<String, Vec<String>>;
+
+/// Tags are matched case-insensitively against the bundled tags, the user's tags
+/// and the character's categories.
+pub fn has_tag(character: &Character, tag: &str, user_tags: &UserTags) -> bool {
+    let tag = tag.trim();
+    character
+        .tags
+        .iter()
+        .chain(user_tags.get(&character.id()).into_iter().flatten())
+        .map(String::as_str)
+        .chain(character.categories.iter().map(|c| c.str()))
+        .any(|t| t.eq_ignore_ascii_case(tag))
+}
+
+/// Limits how many characters (of a type) with a tag may be in a list.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct TagConstraint {
+    pub tag: String,
+    pub r#type: Option<Type>,
+    pub min: Option<u8>,
+    pub max: Option<u8>,
+}
+
+impl TagConstraint {
+    pub fn count(&self, characters: &[&Character], user_tags: &UserTags) -> usize {
+        characters
+            .iter()
+            .filter(|c| self.r#type.is_none_or(|t| c.r#type == t))
+            .filter(|c| has_tag(c, &self.tag, user_tags))
+            .count()
+    }
+
+    pub fn is_satisfied(&self, characters: &[&Character], user_tags: &UserTags) -> bool {
+        // Constraints without a tag are still being edited
+        if self.tag.trim().is_empty() {
+            return true;
+        }
+        let count = self.count(characters, user_tags);
+        self.min.is_none_or(|min| count >= min as usize)
+            && self.max.is_none_or(|max| count <= max as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logic::character::Category;
+
+    #[test]
+    fn tags_match_bundled_user_tags_and_categories() {
+        let chef = Character {
+            tags: vec!["Starter".to_string()],
+            categories: vec![Category::Information],
+            ..Character::test("Chef", Type::Townsfolk)
+        };
+        let user_tags: UserTags = [("chef".to_string(), vec!["favourite".to_string()])].into();
+        assert!(has_tag(&chef, "starter", &user_tags));
+        assert!(has_tag(&chef, " Favourite ", &user_tags));
+        assert!(has_tag(&chef, "information", &user_tags));
+        assert!(!has_tag(&chef, "protection", &user_tags));
+        assert!(!has_tag(&chef, "favourite", &UserTags::new()));
+    }
+
+    #[test]
+    fn constraints_count_characters_of_the_type() {
+        let tagged = |name, r#type| Character {
+            tags: vec!["loud".to_string()],
+            ..Character::test(name, r#type)
+        };
+        let characters = [
+            tagged("Town Crier", Type::Townsfolk),
+            tagged("Gossip", Type::Townsfolk),
+            tagged("Goblin", Type::Minion),
+            Character::test("Chef", Type::Townsfolk),
+        ];
+        let characters: Vec<&Character> = characters.iter().collect();
+        let constraint = |r#type, min, max| TagConstraint {
+            tag: "Loud".to_string(),
+            r#type,
+            min,
+            max,
+        };
+        let user_tags = UserTags::new();
+
+        assert_eq!(
+            constraint(None, None, None).count(&characters, &user_tags),
+            3
+        );
+        assert!(
+            constraint(Some(Type::Townsfolk), Some(2), Some(2))
+                .is_satisfied(&characters, &user_tags)
+        );
+        assert!(!constraint(None, None, Some(2)).is_satisfied(&characters, &user_tags));
+        assert!(
+            !constraint(Some(Type::Minion), Some(2), None).is_satisfied(&characters, &user_tags)
+        );
+        // constraints without a tag are ignored
+        let editing = TagConstraint {
+            max: Some(0),
+            ..Default::default()
+        };
+        assert!(editing.is_satisfied(&characters, &user_tags));
+    }
+}
@@ -0,0 +1,264 @@
+use std::fmt::Write as _;
+
+use super::{character::Character, i18n::Language, seating::seat_count};
+
+/// Sizes in mm, so that the pages print on A4 at the size of the official tokens.
+const PAGE_WIDTH: f64 = 210.0;
+const PAGE_HEIGHT: f64 = 297.0;
+const CHARACTER_RADIUS: f64 = 22.0;
+const REMINDER_RADIUS: f64 = 13.0;
+const GAP: f64 = 4.0;
+
+const BACKGROUND: &str = "#F1EAD9";
+const BORDER: &str = "#5C1F22";
+const SETUP_LEAF: &str = "#D1792B";
+
+/// Printable A4 pages as SVGs, with a token for every seat a character is dealt to
+/// and every reminder of those characters.
+/// `icon_url` maps the icon of a character to what the pages link to,
+/// a data URI keeps the pages self-contained.
+pub fn token_pages(
+    characters: &[&Character],
+    player_count: u8,
+    language: Language,
+    icon_url: impl Fn(&str) -> String,
+) -> Vec<String> {
+    let mut sheet = Sheet {
+        pages: Vec::new(),
+        body: String::new(),
+        y: GAP,
+        ids: 0,
+    };
+
+    let character_tokens: Vec<_> = characters
+        .iter()
+        .flat_map(|&c| {
+            std::iter::repeat_n((c, language.name(c)), seat_count(c, player_count) as usize)
+        })
+        .collect();
+    // Characters that change the setup get a leaf, like the official tokens
+    sheet.grid(CHARACTER_RADIUS, &character_tokens, &icon_url, |c| {
+        c.conditions.as_ref().is_some_and(|c| !c.is_empty())
+    });
+
+    let reminder_tokens: Vec<_> = characters
+        .iter()
+        .flat_map(|&c| language.reminders(c).into_iter().map(move |r| (c, r)))
+        .collect();
+    sheet.grid(REMINDER_RADIUS, &reminder_tokens, &icon_url, |_| false);
+
+    sheet.finish_page();
+    sheet.pages
+}
+
+/// The finished pages and the one that is being filled.
+struct Sheet {
+    pages: Vec<String>,
+    body: String,
+    /// where the next row starts on the current page
+    y: f64,
+    /// ids of the text paths, unique across all pages
+    ids: usize,
+}
+
+impl Sheet {
+    /// Lays out tokens in rows, starting a new page whenever a row doesn't fit.
+    fn grid(
+        &mut self,
+        radius: f64,
+        tokens: &[(&Character, &str)],
+        icon_url: &impl Fn(&str) -> String,
+        has_leaf: impl Fn(&Character) -> bool,
+    ) {
+        let size = 2.0 * radius + GAP;
+        let per_row = (((PAGE_WIDTH - GAP) / size) as usize).max(1);
+        for row in tokens.chunks(per_row) {
+            if self.y + size > PAGE_HEIGHT {
+                self.finish_page();
+            }
+            for (i, &(c, text)) in row.iter().enumerate() {
+                let cx = GAP + radius + i as f64 * size;
+                let cy = self.y + radius;
+                self.ids += 1;
+                token(
+                    &mut self.body,
+                    self.ids,
+                    (cx, cy),
+                    radius,
+                    c.icon.as_deref().map(icon_url).as_deref(),
+                    text,
+                    has_leaf(c),
+                );
+            }
+            self.y += size;
+        }
+    }
+
+    fn finish_page(&mut self) {
+        if self.body.is_empty() {
+            return;
+        }
+        self.pages.push(format!(
+            concat!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" "#,
+                r#"width="{w}mm" height="{h}mm" viewBox="0 0 {w} {h}" font-family="serif">"#,
+                "{body}</svg>"
+            ),
+            w = PAGE_WIDTH,
+            h = PAGE_HEIGHT,
+            body = std::mem::take(&mut self.body),
+        ));
+        self.y = GAP;
+    }
+}
+
+fn token(
+    svg: &mut String,
+    id: usize,
+    (cx, cy): (f64, f64),
+    r: f64,
+    icon: Option<&str>,
+    text: &str,
+    leaf: bool,
+) {
+    let font_size = r / 6.0;
+    // The text runs along the bottom of the token, from left to right
+    let text_radius = r - font_size * 1.4;
+    let _ = write!(
+        svg,
+        r#"<g><circle cx="{cx}" cy="{cy}" r="{r}" fill="{BACKGROUND}" stroke="{BORDER}" stroke-width="0.5"/>"#
+    );
+    if leaf {
+        let top = cy - r + 1.0;
+        let _ = write!(
+            svg,
+            r#"<path d="M {cx} {top} q {w} {h} 0 {l} q -{w} -{h} 0 -{l} z" fill="{SETUP_LEAF}"/>"#,
+            w = r / 8.0,
+            h = r / 8.0,
+            l = r / 4.0,
+        );
+    }
+    if let Some(icon) = icon {
+        let size = r * 1.2;
+        let _ = write!(
+            svg,
+            r#"<image xlink:href="{}" x="{}" y="{}" width="{size}" height="{size}"/>"#,
+            escape(icon),
+            cx - size / 2.0,
+            cy - size * 0.6,
+        );
+    }
+    let _ = write!(
+        svg,
+        concat!(
+            r#"<path id="t{id}" d="M {x1} {cy} A {tr} {tr} 0 0 0 {x2} {cy}" fill="none"/>"#,
+            r#"<text font-size="{font_size}" text-anchor="middle" fill="black">"#,
+            r##"<textPath xlink:href="#t{id}" startOffset="50%">{text}</textPath></text></g>"##,
+        ),
+        id = id,
+        x1 = cx - text_radius,
+        x2 = cx + text_radius,
+        cy = cy,
+        tr = text_radius,
+        font_size = font_size,
+        text = escape(text),
+    );
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logic::{character::Type, condition::Condition};
+
+    fn pages(characters: &[Character]) -> Vec<String> {
+        let characters: Vec<&Character> = characters.iter().collect();
+        token_pages(&characters, 10, Language::English, |url| url.to_string())
+    }
+
+    /// (cy, r) of every token on the page.
+    fn circles(page: &str) -> Vec<(f64, f64)> {
+        let attribute = |circle: &str, name: &str| -> f64 {
+            let value = circle.split(&format!(" {name}=\"")).nth(1).unwrap();
+            value.split('"').next().unwrap().parse().unwrap()
+        };
+        page.split("<circle")
+            .skip(1)
+            .map(|circle| (attribute(circle, "cy"), attribute(circle, "r")))
+            .collect()
+    }
+
+    #[test]
+    fn escapes_text_and_icons() {
+        let character = Character {
+            icon: Some("icon.png?a=1&b=\"2\"".to_string()),
+            reminders: vec!["<b>Dead</b>".to_string()],
+            ..Character::test("Lil' Monsta & Co", Type::Demon)
+        };
+        let page = &pages(&[character])[0];
+        assert!(page.contains("Lil&apos; Monsta &amp; Co"));
+        assert!(page.contains("&lt;b&gt;Dead&lt;/b&gt;"));
+        assert!(page.contains(r#"xlink:href="icon.png?a=1&amp;b=&quot;2&quot;""#));
+        assert!(!page.contains("<b>"));
+    }
+
+    #[test]
+    fn splits_tokens_into_a4_pages() {
+        let characters: Vec<Character> = (1..=30)
+            .map(|i| Character {
+                reminders: vec!["Reminder".to_string(); 3],
+                ..Character::test(&format!("Character {i}"), Type::Townsfolk)
+            })
+            .collect();
+        let pages = pages(&characters);
+        assert!(pages.len() > 1);
+
+        let mut tokens = 0;
+        for page in &pages {
+            assert!(page.contains(r#"width="210mm" height="297mm""#));
+            for (cy, r) in circles(page) {
+                assert!(cy - r >= 0.0 && cy + r <= PAGE_HEIGHT, "{cy} {r}");
+                tokens += 1;
+            }
+        }
+        assert_eq!(tokens, 30 + 30 * 3);
+    }
+
+    #[test]
+    fn one_token_per_seat() {
+        let legion = Character {
+            reminders: vec!["About to die".to_string()],
+            ..Character::test("Legion", Type::Demon).with_conditions(r#"[{"seats": "MAJORITY"}]"#)
+        };
+        let page = &pages(&[legion])[0];
+        // 6 of the 10 players are Legion, the reminder is only printed once
+        assert_eq!(page.matches(">Legion</textPath>").count(), 6);
+        assert_eq!(page.matches(">About to die</textPath>").count(), 1);
+    }
+
+    #[test]
+    fn only_characters_with_conditions_get_a_leaf() {
+        let leaf = format!(r#"fill="{SETUP_LEAF}""#);
+        let plain = Character {
+            reminders: vec!["Reminder".to_string()],
+            ..Character::test("Plain", Type::Townsfolk)
+        };
+        assert!(!pages(std::slice::from_ref(&plain))[0].contains(&leaf));
+
+        let conditioned = Character {
+            conditions: Some(vec![Condition::Character {
+                character: "plain".to_string(),
+            }]),
+            ..plain
+        };
+        // the reminder of the character has no leaf
+        assert_eq!(pages(&[conditioned])[0].matches(&leaf).count(), 1);
+    }
+}
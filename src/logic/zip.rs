@@ -0,0 +1,129 @@
+/// Packs files into an uncompressed zip archive, so that several files can be
+/// downloaded at once.
+pub fn zip(files: &[(String, Vec<u8>)]) -> Vec<u8> {
+    // 1980-01-01 00:00, the earliest date zip can store
+    const TIME: u16 = 0;
+    const DATE: u16 = 0x21;
+
+    let mut archive = Vec::new();
+    let mut directory = Vec::new();
+    for (name, content) in files {
+        let offset = archive.len() as u32;
+        let crc = crc32(content);
+        let size = content.len() as u32;
+        let name = name.as_bytes();
+
+        // local file header
+        archive.extend(0x04034b50u32.to_le_bytes());
+        archive.extend(20u16.to_le_bytes()); // version needed to extract
+        archive.extend(0u16.to_le_bytes()); // flags
+        archive.extend(0u16.to_le_bytes()); // stored without compression
+        archive.extend(TIME.to_le_bytes());
+        archive.extend(DATE.to_le_bytes());
+        archive.extend(crc.to_le_bytes());
+        archive.extend(size.to_le_bytes()); // compressed size
+        archive.extend(size.to_le_bytes()); // uncompressed size
+        archive.extend((name.len() as u16).to_le_bytes());
+        archive.extend(0u16.to_le_bytes()); // extra field length
+        archive.extend(name);
+        archive.extend(content);
+
+        // central directory entry
+        directory.extend(0x02014b50u32.to_le_bytes());
+        directory.extend(20u16.to_le_bytes()); // version made by
+        directory.extend(20u16.to_le_bytes()); // version needed to extract
+        directory.extend(0u16.to_le_bytes()); // flags
+        directory.extend(0u16.to_le_bytes()); // stored without compression
+        directory.extend(TIME.to_le_bytes());
+        directory.extend(DATE.to_le_bytes());
+        directory.extend(crc.to_le_bytes());
+        directory.extend(size.to_le_bytes());
+        directory.extend(size.to_le_bytes());
+        directory.extend((name.len() as u16).to_le_bytes());
+        directory.extend(0u16.to_le_bytes()); // extra field length
+        directory.extend(0u16.to_le_bytes()); // comment length
+        directory.extend(0u16.to_le_bytes()); // disk number
+        directory.extend(0u16.to_le_bytes()); // internal attributes
+        directory.extend(0u32.to_le_bytes()); // external attributes
+        directory.extend(offset.to_le_bytes());
+        directory.extend(name);
+    }
+
+    let directory_offset = archive.len() as u32;
+    let count = files.len() as u16;
+    archive.extend(&directory);
+    // end of central directory record
+    archive.extend(0x06054b50u32.to_le_bytes());
+    archive.extend(0u16.to_le_bytes()); // this disk
+    archive.extend(0u16.to_le_bytes()); // disk with the central directory
+    archive.extend(count.to_le_bytes()); // entries on this disk
+    archive.extend(count.to_le_bytes()); // entries in total
+    archive.extend((directory.len() as u32).to_le_bytes());
+    archive.extend(directory_offset.to_le_bytes());
+    archive.extend(0u16.to_le_bytes()); // comment length
+    archive
+}
+
+/// CRC-32 as used by zip (IEEE 802.3, reflected).
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB88320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn u16_at(bytes: &[u8], i: usize) -> u16 {
+        u16::from_le_bytes(bytes[i..i + 2].try_into().unwrap())
+    }
+
+    fn u32_at(bytes: &[u8], i: usize) -> u32 {
+        u32::from_le_bytes(bytes[i..i + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn crc32_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF43926);
+        assert_eq!(crc32(b""), 0);
+    }
+
+    #[test]
+    fn stores_every_file() {
+        let files = [
+            ("a.svg".to_string(), b"<svg/>".to_vec()),
+            ("b.svg".to_string(), b"<svg></svg>".to_vec()),
+        ];
+        let archive = zip(&files);
+
+        let end = archive.len() - 22;
+        assert_eq!(u32_at(&archive, end), 0x06054b50);
+        assert_eq!(u16_at(&archive, end + 10), 2);
+        let directory_size = u32_at(&archive, end + 12) as usize;
+        let mut entry = u32_at(&archive, end + 16) as usize;
+        assert_eq!(entry + directory_size, end);
+
+        for (name, content) in &files {
+            assert_eq!(u32_at(&archive, entry), 0x02014b50);
+            assert_eq!(u32_at(&archive, entry + 16), crc32(content));
+            let name_len = u16_at(&archive, entry + 28) as usize;
+            assert_eq!(&archive[entry + 46..entry + 46 + name_len], name.as_bytes());
+
+            let local = u32_at(&archive, entry + 42) as usize;
+            assert_eq!(u32_at(&archive, local), 0x04034b50);
+            let start = local + 30 + name_len;
+            assert_eq!(&archive[start..start + content.len()], content.as_slice());
+            entry += 46 + name_len;
+        }
+    }
+}
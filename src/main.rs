@@ -2,11 +2,26 @@
 
 mod app;
 mod consts;
+mod icons;
 mod logic {
+    pub mod analysis;
+    pub mod balance;
     pub mod character;
     pub mod condition;
     pub mod data;
+    pub mod distribution;
+    pub mod editor;
+    pub mod i18n;
+    pub mod lint;
+    pub mod notes;
+    pub mod qr;
+    pub mod reveal;
+    pub mod seating;
     pub mod state;
+    pub mod statistics;
+    pub mod tags;
+    pub mod tokens;
+    pub mod zip;
 }
 
 use tracing_subscriber::fmt::format::Pretty;